@@ -1,7 +1,10 @@
-use std::ffi::OsString;
+use std::{ffi::OsString, path::PathBuf, time::Duration};
 
-use crate::repository::NodeVersion;
-use clap::{Parser, Subcommand};
+use crate::{
+    repository::{InstallSource, NodeVersion},
+    utils::{parse_age, parse_date},
+};
+use clap::{builder::FalseyValueParser, Parser, Subcommand};
 
 #[derive(Clone, Debug, Parser)]
 #[clap(infer_subcommands = true)]
@@ -14,6 +17,10 @@ pub struct Args {
     #[arg(long)]
     pub use_version: Option<NodeVersion>,
 
+    /// Never accesses the network and only uses installed versions and cached data
+    #[arg(long, env = "NENV_OFFLINE", value_parser = FalseyValueParser::new())]
+    pub offline: bool,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -28,7 +35,7 @@ pub enum Command {
     #[command()]
     Init,
 
-    /// Installs the given node version
+    /// Installs the given node versions or archives
     #[command()]
     Install(InstallArgs),
 
@@ -47,17 +54,37 @@ pub enum Command {
     #[command()]
     RemapBinaries,
 
+    /// Refreshes the list of available versions and remaps all binaries
+    #[command()]
+    Refresh,
+
     /// Lists all available versions
     #[command(name = "list-versions")]
     ListVersions,
 
+    /// Lists the versions that are available for installation
+    #[command(name = "ls-remote")]
+    LsRemote(LsRemoteArgs),
+
+    /// Reports installed, default and pinned versions with newer security releases
+    #[command()]
+    Audit,
+
     /// Executes the given version specific  node executable
     #[command()]
     Exec(ExecArgs),
 
     /// Clears the download cache
     #[command()]
-    ClearCache,
+    ClearCache(ClearCacheArgs),
+
+    /// Manages the download cache
+    #[command()]
+    Cache(CacheArgs),
+
+    /// Creates and imports bundles of node versions for machines without network access
+    #[command()]
+    Bundle(BundleArgs),
 
     /// Pins binary to a specific node version
     #[command()]
@@ -66,6 +93,18 @@ pub enum Command {
     /// Unpins a command
     #[command()]
     Unpin(UnpinArgs),
+
+    /// Manages user defined names for node versions
+    #[command()]
+    Alias(AliasArgs),
+
+    /// Manages the keys used to verify release signatures
+    #[command()]
+    Keys(KeysArgs),
+
+    /// Manages the configured download mirrors
+    #[command()]
+    Mirrors(MirrorsArgs),
 }
 
 #[derive(Clone, Debug, Parser)]
@@ -95,8 +134,9 @@ pub struct UnpinArgs {
 
 #[derive(Clone, Debug, Parser)]
 pub struct InstallArgs {
-    /// the version to install
-    pub version: NodeVersion,
+    /// the versions, archive files or archive urls to install
+    #[arg(required = true)]
+    pub sources: Vec<InstallSource>,
 }
 
 #[derive(Clone, Debug, Parser)]
@@ -110,3 +150,185 @@ pub struct DefaultArgs {
     /// The version to set as default
     pub version: NodeVersion,
 }
+
+#[derive(Clone, Debug, Parser)]
+pub struct LsRemoteArgs {
+    /// Only lists lts versions
+    #[arg(long)]
+    pub lts: bool,
+
+    /// Only lists versions of the lts line with the given name like `hydrogen`
+    #[arg(long)]
+    pub lts_name: Option<String>,
+
+    /// Only lists versions with the given major version
+    #[arg(long)]
+    pub major: Option<u8>,
+
+    /// Only lists releases that contain security fixes
+    #[arg(long)]
+    pub security_only: bool,
+
+    /// Only lists versions released on or after the given date like `2024-01-31`
+    #[arg(long, value_parser = parse_date)]
+    pub since: Option<String>,
+
+    /// Only lists versions with a build for this platform
+    #[arg(long)]
+    pub installable: bool,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct ClearCacheArgs {
+    /// Also removes the cached lists of available versions
+    #[arg(long)]
+    pub versions: bool,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    pub command: CacheCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum CacheCommand {
+    /// Lists all cached files with their size and when they were last used
+    #[command()]
+    List,
+
+    /// Removes cached archives and checksums
+    #[command()]
+    Prune(CachePruneArgs),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct CachePruneArgs {
+    /// Only removes files that haven't been used for the given time like `30d`, `12h` or `2w`
+    #[arg(long, value_parser = parse_age)]
+    pub older_than: Option<Duration>,
+
+    /// Keeps the archives of installed versions
+    #[arg(long)]
+    pub keep_installed: bool,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct BundleArgs {
+    #[command(subcommand)]
+    pub command: BundleCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum BundleCommand {
+    /// Packs the archives of the given versions into a bundle
+    #[command()]
+    Create(BundleCreateArgs),
+
+    /// Imports a bundle into the download cache and the list of available versions
+    #[command()]
+    Import(BundleImportArgs),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct BundleCreateArgs {
+    /// The versions to bundle
+    #[arg(required = true)]
+    pub versions: Vec<NodeVersion>,
+
+    /// The file the bundle is written to
+    #[arg(short, long, default_value = "node-bundle.tar")]
+    pub output: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct BundleImportArgs {
+    /// The bundle file
+    pub path: PathBuf,
+
+    /// Installs the bundled versions after importing them
+    #[arg(long)]
+    pub install: bool,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct AliasArgs {
+    #[command(subcommand)]
+    pub command: AliasCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum AliasCommand {
+    /// Creates or replaces an alias for a version
+    #[command()]
+    Set(AliasSetArgs),
+
+    /// Removes an alias
+    #[command(name = "rm")]
+    Remove(AliasRemoveArgs),
+
+    /// Lists all aliases with the versions they resolve to
+    #[command()]
+    List,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct AliasSetArgs {
+    /// The name of the alias like `work`
+    pub name: String,
+
+    /// The version the alias refers to. It can be another alias.
+    pub version: NodeVersion,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct AliasRemoveArgs {
+    /// The name of the alias
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct KeysArgs {
+    #[command(subcommand)]
+    pub command: KeysCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum KeysCommand {
+    /// Imports all public keys from the given file
+    #[command()]
+    Import(KeyImportArgs),
+
+    /// Lists all known release keys
+    #[command()]
+    List,
+
+    /// Removes an imported key
+    #[command()]
+    Remove(KeyRemoveArgs),
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct KeyImportArgs {
+    /// The file containing armored or binary public keys
+    pub path: PathBuf,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct KeyRemoveArgs {
+    /// The fingerprint or long key id of the key
+    pub fingerprint: String,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct MirrorsArgs {
+    #[command(subcommand)]
+    pub command: MirrorsCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum MirrorsCommand {
+    /// Tests which mirrors are reachable and how fast they respond
+    #[command()]
+    Test,
+}
@@ -9,6 +9,7 @@ use tokio::fs;
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use crate::error::SerializeTomlError;
+use crate::lock::{write_atomic, FileLock};
 use crate::{
     consts::{CFG_DIR, CFG_FILE_PATH},
     error::ParseConfigError,
@@ -78,14 +79,14 @@ impl ConfigAccess {
         }
     }
 
-    pub async fn get(&self) -> RwLockReadGuard<Config> {
+    pub async fn get(&self) -> RwLockReadGuard<'_, Config> {
         if self.dirty.swap(false, std::sync::atomic::Ordering::Relaxed) {
             self.save().await.expect("Failed so save config");
         }
         self.config.read().await
     }
 
-    pub async fn get_mut(&self) -> ModifyGuard<Config> {
+    pub async fn get_mut(&self) -> ModifyGuard<'_, Config> {
         if self.dirty.swap(false, std::sync::atomic::Ordering::Relaxed) {
             self.save().await.expect("Failed so save config");
         }
@@ -100,13 +101,10 @@ impl ConfigAccess {
     }
 
     pub async fn save(&self) -> Result<()> {
-        fs::write(
-            &*CFG_FILE_PATH,
-            toml::to_string_pretty(&*self.config.read().await).map_err(SerializeTomlError::from)?,
-        )
-        .await
-        .into_diagnostic()
-        .context("writing config file")?;
+        let contents =
+            toml::to_string_pretty(&*self.config.read().await).map_err(SerializeTomlError::from)?;
+        let _lock = FileLock::exclusive("config", "writing the config").await?;
+        write_atomic(&CFG_FILE_PATH, contents.as_bytes()).context("writing config file")?;
 
         Ok(())
     }
@@ -1,8 +1,12 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt, path::PathBuf, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{consts::NODE_DIST_URL, repository::NodeVersion};
+use crate::{
+    consts::{NODE_CHANNELS_URL, NODE_DIST_URL, NODE_SCHEDULE_URL, UNOFFICIAL_DIST_URL},
+    repository::NodeVersion,
+    utils::redact_url,
+};
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug)]
 pub struct Config {
@@ -12,9 +16,17 @@ pub struct Config {
     /// Configuration for how to download node versions
     pub download: DownloadConfig,
 
+    /// Configuration of the download cache
+    #[serde(default)]
+    pub cache: CacheConfig,
+
     /// List of executables that are hardwired to a given node version
     /// and can still be executed from other versions with this given version.
     pub bins: HashMap<String, ExecutableConfig>,
+
+    /// User defined names for node versions like `work = "18.19"`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub aliases: HashMap<String, NodeVersion>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -22,11 +34,218 @@ pub struct NodeConfig {
     /// The default version if no version is specified
     /// in the `package.json` file or `NODE_VERSION` environment variable
     pub default_version: NodeVersion,
+
+    /// Whether `latest` and `lts` resolve to the newest installed version
+    /// or to the newest published one
+    #[serde(default)]
+    pub latest_policy: LatestPolicy,
+
+    /// What to do when installing a major version that has reached its end of life
+    #[serde(default)]
+    pub eol_policy: EolPolicy,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DownloadConfig {
+    /// The url of the nodejs dist directory.
+    /// It is only used when no mirrors are configured.
     pub dist_base_url: String,
+
+    /// What to do when the signature of the release checksums
+    /// can't be verified with the known release keys
+    #[serde(default)]
+    pub signature_policy: SignaturePolicy,
+
+    /// The time in seconds after which the cached index of available versions
+    /// is revalidated with the mirror
+    #[serde(default = "default_versions_ttl")]
+    pub versions_ttl: u64,
+
+    /// The build flavor of node to install.
+    /// Defaults to musl builds on musl based systems and official builds otherwise.
+    #[serde(default)]
+    pub flavor: Flavor,
+
+    /// The url of the directory with the unofficial builds.
+    /// It is used for all flavors except the official one
+    /// when no unofficial mirrors are configured.
+    #[serde(default = "default_unofficial_base_url")]
+    pub unofficial_base_url: String,
+
+    /// The url of the directory containing the directories of the
+    /// nightly, rc, test and v8-canary release channels.
+    /// It is only used when no channel mirrors are configured.
+    #[serde(default = "default_channels_base_url")]
+    pub channels_base_url: String,
+
+    /// The url of the `schedule.json` of the nodejs release working group
+    /// that contains the support phases of each major version
+    #[serde(default = "default_schedule_url")]
+    pub schedule_url: String,
+
+    /// The archive format to download.
+    /// By default the smallest format available for the version is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_format: Option<ArchiveFormat>,
+
+    /// Whether downloaded archives are kept in the cache.
+    /// Tar archives are extracted while they are downloaded when disabled.
+    #[serde(default = "default_keep_archives")]
+    pub keep_archives: bool,
+
+    /// Mirrors of the nodejs dist directory.
+    /// They are tried in the given order until one of them is reachable.
+    /// `file://` urls can be used for local directories.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<MirrorConfig>,
+
+    /// Mirrors of the unofficial builds directory used for all flavors except the official one.
+    /// They are tried in the given order like the mirrors of the dist directory.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unofficial_mirrors: Vec<MirrorConfig>,
+
+    /// Mirrors of the directory containing the directories of the release channels.
+    /// They are tried in the given order like the mirrors of the dist directory.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channel_mirrors: Vec<MirrorConfig>,
+
+    /// Configuration of the http client used for all requests
+    #[serde(default)]
+    pub http: HttpConfig,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// The proxy used for all requests.
+    /// Http, https and socks5 proxies are supported.
+    pub proxy: Option<String>,
+
+    /// A comma separated list of hosts that are accessed without the proxy.
+    /// Defaults to the `NO_PROXY` environment variable.
+    pub no_proxy: Option<String>,
+
+    /// Paths to PEM files with additional CA certificates
+    pub ca_certificates: Vec<PathBuf>,
+
+    /// The time in seconds to wait for a connection to be established
+    pub connect_timeout: u64,
+
+    /// The time in seconds to wait for data from the server
+    pub read_timeout: u64,
+
+    /// The number of times a request is retried when the mirror is unavailable
+    pub retries: u32,
+
+    /// A custom user agent sent with all requests
+    pub user_agent: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CacheConfig {
+    /// The maximum size of the cached archives like `2GiB` or `500M`.
+    /// The least recently used archives are removed after a download
+    /// when the cache grows beyond it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<ByteSize>,
+}
+
+/// A number of bytes that is written with a binary unit in the config
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(try_from = "RawByteSize", into = "String")]
+pub struct ByteSize(pub u64);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawByteSize {
+    Bytes(u64),
+    Text(String),
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MirrorConfig {
+    pub url: String,
+
+    /// How to authenticate with the mirror
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<MirrorAuth>,
+}
+
+/// The authentication for a mirror.
+/// Secrets are never stored in the config itself.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum MirrorAuth {
+    /// A bearer token read from the given environment variable
+    Bearer { token_env: String },
+
+    /// Basic auth with the password read from the given environment variable
+    Basic {
+        username: String,
+        password_env: String,
+    },
+
+    /// The credentials for the mirrors host from the `~/.netrc` file
+    Netrc,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LatestPolicy {
+    /// Use the newest installed version
+    #[default]
+    Installed,
+    /// Use the newest published version and prompt to install it
+    /// when it is newer than the installed one
+    Published,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EolPolicy {
+    /// Print a warning and continue with the installation
+    #[default]
+    Warn,
+    /// Abort the installation
+    Refuse,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SignaturePolicy {
+    /// Abort the installation
+    Error,
+    /// Print a warning and continue with the installation
+    #[default]
+    Warn,
+}
+
+/// A variant of the node builds for the same platform
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum Flavor {
+    /// The builds published on nodejs.org
+    Official,
+    /// Builds linked against musl for alpine and other musl based systems
+    Musl,
+    /// Builds linked against glibc 2.17 for older linux distributions
+    #[serde(rename = "glibc-217")]
+    Glibc217,
+    /// Builds with pointer compression enabled
+    PointerCompression,
+    /// Builds for platforms that are only supported by the unofficial builds
+    /// like riscv64 or loong64
+    Unofficial,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    #[serde(rename = "tar.xz")]
+    TarXz,
+    #[serde(rename = "tar.gz")]
+    TarGz,
+    #[serde(rename = "zip")]
+    Zip,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -37,10 +256,21 @@ pub struct ExecutableConfig {
     pub node_version: NodeVersion,
 }
 
+impl fmt::Debug for MirrorConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MirrorConfig")
+            .field("url", &redact_url(&self.url))
+            .field("auth", &self.auth)
+            .finish()
+    }
+}
+
 impl Default for NodeConfig {
     fn default() -> Self {
         Self {
             default_version: NodeVersion::LatestLts,
+            latest_policy: LatestPolicy::default(),
+            eol_policy: EolPolicy::default(),
         }
     }
 }
@@ -49,6 +279,239 @@ impl Default for DownloadConfig {
     fn default() -> Self {
         Self {
             dist_base_url: String::from(NODE_DIST_URL),
+            signature_policy: SignaturePolicy::default(),
+            versions_ttl: default_versions_ttl(),
+            flavor: Flavor::default(),
+            unofficial_base_url: default_unofficial_base_url(),
+            channels_base_url: default_channels_base_url(),
+            schedule_url: default_schedule_url(),
+            archive_format: None,
+            keep_archives: default_keep_archives(),
+            mirrors: Vec::new(),
+            unofficial_mirrors: Vec::new(),
+            channel_mirrors: Vec::new(),
+            http: HttpConfig::default(),
+        }
+    }
+}
+
+impl Flavor {
+    /// Returns the suffix of the platform in the archive names of this flavor
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Flavor::Official | Flavor::Unofficial => "",
+            Flavor::Musl => "-musl",
+            Flavor::Glibc217 => "-glibc-217",
+            Flavor::PointerCompression => "-pointer-compression",
+        }
+    }
+
+    pub fn is_official(&self) -> bool {
+        *self == Flavor::Official
+    }
+
+    /// All flavors in the order they are displayed in
+    pub fn all() -> [Flavor; 5] {
+        [
+            Flavor::Official,
+            Flavor::Musl,
+            Flavor::Glibc217,
+            Flavor::PointerCompression,
+            Flavor::Unofficial,
+        ]
+    }
+}
+
+impl Default for Flavor {
+    fn default() -> Self {
+        if cfg!(target_env = "musl") {
+            Flavor::Musl
+        } else if cfg!(any(target_arch = "riscv64", target_arch = "loongarch64")) {
+            Flavor::Unofficial
+        } else {
+            Flavor::Official
+        }
+    }
+}
+
+impl fmt::Display for Flavor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Flavor::Official => "official",
+            Flavor::Musl => "musl",
+            Flavor::Glibc217 => "glibc-217",
+            Flavor::PointerCompression => "pointer-compression",
+            Flavor::Unofficial => "unofficial",
+        }
+        .fmt(f)
+    }
+}
+
+impl ArchiveFormat {
+    /// Returns the file extension of archives in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::TarXz => "tar.xz",
+            ArchiveFormat::TarGz => "tar.gz",
+            ArchiveFormat::Zip => "zip",
+        }
+    }
+
+    /// Detects the format of an archive from its file name
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+
+        if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+            Some(ArchiveFormat::TarXz)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.extension().fmt(f)
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len());
+        let (amount, unit) = s.split_at(split);
+        let amount = amount
+            .parse::<f64>()
+            .map_err(|_| format!("invalid size `{s}`"))?;
+        let exponent = match unit.trim().to_lowercase().as_str() {
+            "" | "b" => 0,
+            "k" | "kb" | "kib" => 1,
+            "m" | "mb" | "mib" => 2,
+            "g" | "gb" | "gib" => 3,
+            "t" | "tb" | "tib" => 4,
+            unit => return Err(format!("unknown size unit `{unit}`")),
+        };
+
+        let bytes = amount * 1024f64.powi(exponent);
+
+        if bytes >= u64::MAX as f64 {
+            return Err(format!("size `{s}` is too large"));
+        }
+
+        Ok(Self(bytes as u64))
+    }
+}
+
+impl TryFrom<RawByteSize> for ByteSize {
+    type Error = String;
+
+    fn try_from(value: RawByteSize) -> Result<Self, Self::Error> {
+        match value {
+            RawByteSize::Bytes(bytes) => Ok(Self(bytes)),
+            RawByteSize::Text(text) => text.parse(),
+        }
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let units = [("TiB", 4), ("GiB", 3), ("MiB", 2), ("KiB", 1)];
+        let exact = units.iter().find_map(|(unit, exponent)| {
+            let factor = 1024u64.pow(*exponent);
+            (self.0 >= factor && self.0.is_multiple_of(factor)).then(|| (self.0 / factor, unit))
+        });
+
+        match exact {
+            Some((amount, unit)) => write!(f, "{amount}{unit}"),
+            None => write!(f, "{}B", self.0),
+        }
+    }
+}
+
+impl From<ByteSize> for String {
+    fn from(value: ByteSize) -> Self {
+        value.to_string()
+    }
+}
+
+fn default_unofficial_base_url() -> String {
+    String::from(UNOFFICIAL_DIST_URL)
+}
+
+fn default_channels_base_url() -> String {
+    String::from(NODE_CHANNELS_URL)
+}
+
+fn default_schedule_url() -> String {
+    String::from(NODE_SCHEDULE_URL)
+}
+
+fn default_keep_archives() -> bool {
+    true
+}
+
+fn default_versions_ttl() -> u64 {
+    60 * 60
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            no_proxy: None,
+            ca_certificates: Vec::new(),
+            connect_timeout: 10,
+            read_timeout: 30,
+            retries: 2,
+            user_agent: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_sizes_with_units() {
+        let size = |s: &str| s.parse::<ByteSize>().map(|s| s.0);
+
+        assert_eq!(size("512"), Ok(512));
+        assert_eq!(size("512B"), Ok(512));
+        assert_eq!(size("4k"), Ok(4 * 1024));
+        assert_eq!(size(" 1.5 MiB "), Ok(1536 * 1024));
+        assert_eq!(size("2GB"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(size("1t"), Ok(1024u64.pow(4)));
+    }
+
+    #[test]
+    fn it_rejects_invalid_sizes() {
+        assert!("".parse::<ByteSize>().is_err());
+        assert!("GiB".parse::<ByteSize>().is_err());
+        assert!("1.2.3G".parse::<ByteSize>().is_err());
+        assert!("-1G".parse::<ByteSize>().is_err());
+        assert!("10 PiB".parse::<ByteSize>().is_err());
+        assert!("20000000T".parse::<ByteSize>().is_err());
+    }
+
+    #[test]
+    fn it_displays_sizes_with_the_largest_exact_unit() {
+        assert_eq!(ByteSize(0).to_string(), "0B");
+        assert_eq!(ByteSize(1000).to_string(), "1000B");
+        assert_eq!(ByteSize(1024).to_string(), "1KiB");
+        assert_eq!(ByteSize(1536 * 1024).to_string(), "1536KiB");
+        assert_eq!(ByteSize(5 * 1024u64.pow(3)).to_string(), "5GiB");
+
+        for size in [ByteSize(1536 * 1024), ByteSize(5 * 1024u64.pow(3))] {
+            assert_eq!(size.to_string().parse::<ByteSize>(), Ok(size));
         }
     }
 }
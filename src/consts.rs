@@ -2,6 +2,13 @@ use lazy_static::lazy_static;
 use std::path::PathBuf;
 
 pub const NODE_DIST_URL: &str = "https://nodejs.org/dist";
+pub const UNOFFICIAL_DIST_URL: &str = "https://unofficial-builds.nodejs.org/download/release";
+pub const NODE_CHANNELS_URL: &str = "https://nodejs.org/download";
+pub const NODE_SCHEDULE_URL: &str =
+    "https://raw.githubusercontent.com/nodejs/Release/main/schedule.json";
+/// Identifies the layout of the binary cache files.
+/// It has to be changed whenever the layout of the cached data changes.
+pub const CACHE_FORMAT: u64 = 0x6e65_6e76_0000_0005;
 #[cfg(not(windows))]
 pub const SEARCH_PATH_SEPARATOR: &str = ":";
 #[cfg(windows)]
@@ -22,7 +29,9 @@ lazy_static! {
     pub static ref INSTALLED_VERSION_FILE: PathBuf = DATA_DIR.join("installed_versions");
     pub static ref BIN_DIR: PathBuf = DATA_DIR.join("bin");
     pub static ref NODE_VERSIONS_DIR: PathBuf = DATA_DIR.join("versions");
-    pub static ref NODE_ARCHIVE_SUFFIX: String = format!("-{OS}-{ARCH}.{ARCHIVE_TYPE}");
+    pub static ref KEYRING_DIR: PathBuf = DATA_DIR.join("keyring");
+    pub static ref LOCKS_DIR: PathBuf = DATA_DIR.join("locks");
+    pub static ref NOTICES_FILE: PathBuf = DATA_DIR.join("notices.json");
 }
 
 macro_rules! map_arch {
@@ -42,12 +51,17 @@ map_arch!(
     "x86" => "x86",
     "arm" => "armv7l",
     "aarch64" => "arm64",
-    "riscv32" => "armv7l",
-    "powerpc64" => "ppc64",
-    "powerpc64le" => "ppc64le",
+    "riscv64" => "riscv64",
+    "loongarch64" => "loong64",
     "s390x" => "s390x",
 );
 
+#[cfg(all(target_arch = "powerpc64", target_endian = "big"))]
+pub const ARCH: &str = "ppc64";
+
+#[cfg(all(target_arch = "powerpc64", target_endian = "little"))]
+pub const ARCH: &str = "ppc64le";
+
 macro_rules! map_os {
     ($($os:literal => $node_os: literal),+) => {
         map_arch!($($os => $node_os,)+);
@@ -65,9 +79,3 @@ map_os!(
     "windows" => "win",
     "macos" => "darwin",
 );
-
-#[cfg(not(target_os = "windows"))]
-pub const ARCHIVE_TYPE: &str = "tar.gz";
-
-#[cfg(target_os = "windows")]
-pub const ARCHIVE_TYPE: &'static str = "zip";
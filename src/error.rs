@@ -2,6 +2,8 @@ use std::{ffi::OsString, path::PathBuf};
 
 use miette::{Diagnostic, NamedSource, SourceSpan};
 
+use crate::consts::{ARCH, OS};
+
 use thiserror::Error;
 
 #[derive(Debug, Error, Diagnostic)]
@@ -47,6 +49,13 @@ impl VersionError {
         Self::new(src, "The version is not installed.")
     }
 
+    pub fn unavailable_for_platform<S: ToString>(src: S) -> Self {
+        Self::new(
+            src,
+            format!("No release matching this version is available for {OS}-{ARCH}."),
+        )
+    }
+
     pub fn unsupported<S: ToString>(src: S) -> Self {
         Self::new(
             src,
@@ -97,7 +106,6 @@ impl ParseConfigError {
             .line_col()
             .map(|(l, c)| {
                 src.lines()
-                    .into_iter()
                     .take(l)
                     .map(|line| line.len() + 1)
                     .sum::<usize>()
@@ -125,6 +133,221 @@ pub struct SerializeTomlError {
 #[error("http request failed")]
 pub struct ReqwestError(#[from] reqwest::Error);
 
+#[derive(Debug, Error, Diagnostic)]
+pub enum FetchError {
+    #[diagnostic(code(nenv::fetch::request))]
+    #[error("Request to {url} failed: {caused_by}")]
+    Request {
+        url: String,
+        caused_by: reqwest::Error,
+    },
+
+    #[diagnostic(code(nenv::fetch::timeout))]
+    #[error("Request to {url} timed out")]
+    Timeout { url: String },
+
+    #[diagnostic(
+        code(nenv::fetch::auth),
+        help("Check the `auth` settings of the mirror in the config.")
+    )]
+    #[error("Failed to authenticate with {url}: {reason}")]
+    Auth { url: String, reason: String },
+
+    #[diagnostic(
+        code(nenv::fetch::offline),
+        help("Run the command without `--offline` and make sure `NENV_OFFLINE` isn't set.")
+    )]
+    #[error("Fetching `{path}` requires network access but nenv is running in offline mode.")]
+    Offline { path: String },
+
+    #[diagnostic(code(nenv::fetch::status))]
+    #[error("{url} responded with {status}")]
+    Status {
+        url: String,
+        status: reqwest::StatusCode,
+    },
+
+    #[diagnostic(code(nenv::fetch::io))]
+    #[error("Failed to read {url}: {caused_by}")]
+    Io {
+        url: String,
+        /// whether the directory of the mirror could be accessed
+        available: bool,
+        #[source]
+        caused_by: std::io::Error,
+    },
+}
+
+impl FetchError {
+    /// Returns if the error is caused by the mirror being unavailable
+    /// so that the request should be tried on the next one
+    pub fn is_mirror_unavailable(&self) -> bool {
+        match self {
+            FetchError::Request { .. } | FetchError::Timeout { .. } => true,
+            FetchError::Status { status, .. } => status.is_server_error(),
+            FetchError::Io { available, .. } => !available,
+            FetchError::Auth { .. } | FetchError::Offline { .. } => false,
+        }
+    }
+
+    pub fn status(&self) -> Option<reqwest::StatusCode> {
+        match self {
+            FetchError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[diagnostic(
+    code(nenv::download::checksum),
+    help("The archive might be corrupted or tampered with. Try downloading it again.")
+)]
+#[error("The checksum of `{file_name}` doesn't match the published one.\n  expected: {expected}\n  actual:   {actual}")]
+pub struct ChecksumMismatchError {
+    pub file_name: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[diagnostic(
+    code(nenv::download::checksum_missing),
+    help("Check if the configured mirror provides a complete SHASUMS256.txt file.")
+)]
+#[error("No published checksum found for `{file_name}`.")]
+pub struct MissingChecksumError {
+    pub file_name: String,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[diagnostic(
+    code(nenv::download::archive_format),
+    help("Check if the version is available for your platform or change `download.archive_format` in the config.")
+)]
+#[error("No {formats} archive of node v{version} is available for {platform}.")]
+pub struct UnavailableArchiveError {
+    pub version: String,
+    pub formats: String,
+    pub platform: String,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[diagnostic(
+    code(nenv::install::validation),
+    help("The archive might be corrupted or built for a different platform.")
+)]
+#[error("The extracted build of node v{version} is invalid: {reason}")]
+pub struct InvalidBuildError {
+    pub version: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum ImportError {
+    #[diagnostic(
+        code(nenv::import::format),
+        help("Only .tar.xz, .tar.gz and .zip archives can be installed.")
+    )]
+    #[error("The format of `{archive}` isn't supported.")]
+    UnknownFormat { archive: String },
+
+    #[diagnostic(
+        code(nenv::import::layout),
+        help("Make sure the archive is a node release archive.")
+    )]
+    #[error("`{archive}` doesn't contain a node executable.")]
+    MissingExecutable { archive: String },
+
+    #[diagnostic(
+        code(nenv::import::version),
+        help("Make sure the archive is built for this platform and contains the node headers.")
+    )]
+    #[error("The node version in `{archive}` could not be detected.")]
+    UnknownVersion { archive: String },
+}
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum BundleError {
+    #[diagnostic(
+        code(nenv::bundle::manifest),
+        help("Make sure the file has been created with `nenv bundle create`.")
+    )]
+    #[error("`{bundle}` doesn't contain a valid bundle manifest: {reason}")]
+    InvalidManifest { bundle: String, reason: String },
+
+    #[diagnostic(
+        code(nenv::bundle::platform),
+        help("Create the bundle on a machine with the same platform and `download.flavor`.")
+    )]
+    #[error(
+        "The bundle contains builds for {bundled} but {current} builds are used on this machine."
+    )]
+    PlatformMismatch { bundled: String, current: String },
+
+    #[diagnostic(
+        code(nenv::bundle::integrity),
+        help("The bundle might have been damaged while it was copied. Try copying it again.")
+    )]
+    #[error("`{file}` in the bundle {reason}.")]
+    Corrupted { file: String, reason: String },
+}
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum AliasError {
+    #[diagnostic(
+        code(nenv::alias::cycle),
+        help("Change one of the aliases with `nenv alias set` so that it refers to a version.")
+    )]
+    #[error("The alias `{name}` can't be resolved because its aliases form a cycle: {chain}")]
+    Cycle { name: String, chain: String },
+
+    #[diagnostic(
+        code(nenv::alias::shadowing),
+        help("Remove the alias with `nenv alias rm {name}` and choose a different name.")
+    )]
+    #[error("The alias `{name}` shadows the lts codename `{name}`.")]
+    ShadowsCodename { name: String },
+
+    #[diagnostic(
+        code(nenv::alias::reserved),
+        help("Alias names can't be versions, version requirements, channels, `latest` or `lts`.")
+    )]
+    #[error("`{name}` can't be used as an alias name because it is a version.")]
+    Reserved { name: String },
+
+    #[diagnostic(
+        code(nenv::alias::unknown),
+        help("List all aliases with `nenv alias list`.")
+    )]
+    #[error("There is no alias named `{name}`.")]
+    Unknown { name: String },
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[diagnostic(
+    code(nenv::download::signature),
+    help("Import the release signing key with `nenv keys import <file>` or set `download.signature_policy = \"warn\"` in the config.")
+)]
+#[error("The signature of the checksums for node v{version} could not be verified: {reason}")]
+pub struct SignatureVerificationError {
+    pub version: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[diagnostic(
+    code(nenv::version::eol),
+    help("Install a supported version or set `node.eol_policy = \"warn\"` in the config.")
+)]
+#[error(
+    "Node {major} has reached its end of life on {end} and no longer receives security updates."
+)]
+pub struct EndOfLifeError {
+    pub major: u8,
+    pub end: String,
+}
+
 #[derive(Debug, Error, Diagnostic)]
 #[diagnostic(
     code(nenv::exec::command),
@@ -179,6 +402,18 @@ pub struct MapDirError {
     pub caused_by: std::io::Error,
 }
 
+#[derive(Debug, Error, Diagnostic)]
+#[diagnostic(
+    code(nenv::detect::nvmrc),
+    help("Use `lts/*`, the codename of a lts line like `lts/iron` or the number of lts lines before the latest one like `lts/-1`.")
+)]
+#[error("`{value}` in {path:?} isn't a valid lts version: {reason}")]
+pub struct InvalidNvmrcError {
+    pub path: PathBuf,
+    pub value: String,
+    pub reason: String,
+}
+
 pub fn find_in_args(query: &str) -> Option<(String, SourceSpan)> {
     let args_string = std::env::args().fold(String::new(), |s, acc| format!("{s} {acc}"));
 
@@ -0,0 +1,142 @@
+use std::{
+    fmt::Display,
+    fs::{self, File, OpenOptions, TryLockError},
+    io::Write,
+    path::{Path, PathBuf},
+    process,
+};
+
+use miette::{Context, IntoDiagnostic, Result};
+
+use crate::consts::LOCKS_DIR;
+
+/// An advisory lock on a file in the locks directory that is shared
+/// by all nenv processes. The lock is released when it is dropped.
+pub struct FileLock {
+    file: File,
+    waited: bool,
+}
+
+impl FileLock {
+    /// Acquires the exclusive lock with the given name.
+    /// The activity describes what the process holding the lock is doing
+    /// and is printed while waiting for it.
+    #[tracing::instrument(level = "debug", skip(activity))]
+    pub async fn exclusive<D: Display>(name: &str, activity: D) -> Result<Self> {
+        Self::acquire(name, activity, false).await
+    }
+
+    /// Acquires the lock with the given name in shared mode.
+    /// Shared locks can be held by multiple processes at once
+    /// and only wait for exclusive locks.
+    #[tracing::instrument(level = "debug", skip(activity))]
+    pub async fn shared<D: Display>(name: &str, activity: D) -> Result<Self> {
+        Self::acquire(name, activity, true).await
+    }
+
+    /// Acquires the exclusive lock with the given name without waiting.
+    /// Returns `None` if it is held by another process.
+    pub fn try_exclusive(name: &str) -> Result<Option<Self>> {
+        let file = open_lock_file(name)?;
+
+        match file.try_lock() {
+            Ok(_) => Ok(Some(Self {
+                file,
+                waited: false,
+            })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e)
+                .into_diagnostic()
+                .with_context(|| format!("Acquiring lock {name}")),
+        }
+    }
+
+    async fn acquire<D: Display>(name: &str, activity: D, shared: bool) -> Result<Self> {
+        let file = open_lock_file(name)?;
+        let result = if shared {
+            file.try_lock_shared()
+        } else {
+            file.try_lock()
+        };
+
+        match result {
+            Ok(_) => Ok(Self {
+                file,
+                waited: false,
+            }),
+            Err(TryLockError::WouldBlock) => {
+                eprintln!("Waiting for another nenv process {activity}...");
+                let file = tokio::task::spawn_blocking(move || {
+                    if shared {
+                        file.lock_shared()
+                    } else {
+                        file.lock()
+                    }
+                    .map(|_| file)
+                })
+                .await
+                .into_diagnostic()?
+                .into_diagnostic()
+                .with_context(|| format!("Waiting for lock {name}"))?;
+
+                Ok(Self { file, waited: true })
+            }
+            Err(TryLockError::Error(e)) => Err(e)
+                .into_diagnostic()
+                .with_context(|| format!("Acquiring lock {name}")),
+        }
+    }
+
+    /// Returns if the lock was held by another process when it was requested
+    pub fn waited(&self) -> bool {
+        self.waited
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Err(e) = self.file.unlock() {
+            tracing::error!("Failed to release lock: {e}");
+        }
+    }
+}
+
+fn open_lock_file(name: &str) -> Result<File> {
+    fs::create_dir_all(&*LOCKS_DIR)
+        .into_diagnostic()
+        .context("Creating locks directory")?;
+
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path(name))
+        .into_diagnostic()
+        .with_context(|| format!("Opening lock file for {name}"))
+}
+
+pub fn lock_path(name: &str) -> PathBuf {
+    LOCKS_DIR.join(format!("{name}.lock"))
+}
+
+/// Writes the contents to a temporary file that replaces the given file
+/// so that other processes never read a partially written file
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp_path = path.with_file_name(format!(".{file_name}.{}.tmp", process::id()));
+    let mut file = File::create(&tmp_path)
+        .into_diagnostic()
+        .with_context(|| format!("Creating {tmp_path:?}"))?;
+    file.write_all(contents)
+        .and_then(|_| file.sync_all())
+        .into_diagnostic()
+        .with_context(|| format!("Writing {tmp_path:?}"))?;
+    fs::rename(&tmp_path, path)
+        .into_diagnostic()
+        .with_context(|| format!("Replacing {path:?}"))?;
+
+    Ok(())
+}
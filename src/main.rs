@@ -1,12 +1,16 @@
 use std::{env, process};
 
-use args::{Args, PinArgs, UnpinArgs};
+use args::{
+    AliasArgs, AliasCommand, Args, BundleArgs, BundleCommand, CacheArgs, CacheCommand,
+    ClearCacheArgs, KeysArgs, KeysCommand, MirrorsArgs, MirrorsCommand, PinArgs, UnpinArgs,
+};
 use clap::Parser;
 
 use nenv::Nenv;
 
 mod consts;
 pub mod error;
+mod lock;
 pub mod mapper;
 pub mod repository;
 mod utils;
@@ -36,10 +40,11 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    let mut nenv = get_nenv(args.use_version.clone()).await?;
+    repository::staging::register_interrupt_handler()?;
+    let mut nenv = get_nenv(args.use_version.clone(), args.offline).await?;
 
     match args.command {
-        args::Command::Install(v) => nenv.install(v.version).await,
+        args::Command::Install(v) => nenv.install(v.sources).await,
         args::Command::Uninstall(v) => nenv.uninstall(v.version).await,
         args::Command::SetDefault(v) => nenv.set_system_default(v.version).await,
         args::Command::Exec(args) => {
@@ -48,13 +53,39 @@ async fn main() -> Result<()> {
             process::exit(exit_code);
         }
         args::Command::RemapBinaries => nenv.remap().await,
+        args::Command::Refresh => nenv.refresh().await,
         args::Command::ListVersions => nenv.list_versions().await,
+        args::Command::LsRemote(args) => nenv.ls_remote(args).await,
+        args::Command::Audit => nenv.audit().await,
         args::Command::Init => nenv.init_nenv().await,
-        args::Command::ClearCache => nenv.clear_cache().await,
+        args::Command::ClearCache(ClearCacheArgs { versions }) => nenv.clear_cache(versions).await,
+        args::Command::Cache(CacheArgs { command }) => match command {
+            CacheCommand::List => nenv.list_cache().await,
+            CacheCommand::Prune(args) => {
+                nenv.prune_cache(args.older_than, args.keep_installed).await
+            }
+        },
         args::Command::Pin(PinArgs { command, version }) => {
             nenv.pin_command(command, version).await
         }
         args::Command::Unpin(UnpinArgs { command }) => nenv.unpin_command(command).await,
+        args::Command::Bundle(BundleArgs { command }) => match command {
+            BundleCommand::Create(args) => nenv.create_bundle(args.versions, args.output).await,
+            BundleCommand::Import(args) => nenv.import_bundle(args.path, args.install).await,
+        },
+        args::Command::Alias(AliasArgs { command }) => match command {
+            AliasCommand::Set(args) => nenv.set_alias(args.name, args.version).await,
+            AliasCommand::Remove(args) => nenv.remove_alias(args.name).await,
+            AliasCommand::List => nenv.list_aliases().await,
+        },
+        args::Command::Keys(KeysArgs { command }) => match command {
+            KeysCommand::Import(args) => nenv.import_keys(args.path).await,
+            KeysCommand::List => nenv.list_keys().await,
+            KeysCommand::Remove(args) => nenv.remove_key(args.fingerprint).await,
+        },
+        args::Command::Mirrors(MirrorsArgs { command }) => match command {
+            MirrorsCommand::Test => nenv.test_mirrors().await,
+        },
         _ => xkcd_unreachable!(),
     }?;
 
@@ -67,8 +98,8 @@ fn print_version() {
     println!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
 }
 
-async fn get_nenv(version_override: Option<NodeVersion>) -> Result<Nenv> {
-    Nenv::init(version_override).await
+async fn get_nenv(version_override: Option<NodeVersion>, offline: bool) -> Result<Nenv> {
+    Nenv::init(version_override, offline).await
 }
 
 fn init_tracing() {
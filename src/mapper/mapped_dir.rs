@@ -75,9 +75,7 @@ pub async fn map_direct(paths: Vec<PathBuf>) -> Result<()> {
             .map(|n| async move { n.map_executable().await }),
     )
     .await;
-    results
-        .into_iter()
-        .fold(Result::Ok(()), |acc, res| acc.and(res))?;
+    results.into_iter().try_fold((), |_, res| res)?;
 
     Ok(())
 }
@@ -1,71 +1,195 @@
 use crate::{
-    config::{ConfigAccess, ExecutableConfig},
-    consts::{BIN_DIR, CACHE_DIR, VERSION_FILE_PATH},
-    error::VersionError,
+    args::LsRemoteArgs,
+    config::{ConfigAccess, ExecutableConfig, LatestPolicy},
+    consts::{BIN_DIR, CACHE_DIR, NOTICES_FILE},
+    error::{AliasError, VersionError},
+    lock::{write_atomic, FileLock},
     mapper::Mapper,
-    repository::{node_path::NodePath, NodeVersion, Repository},
-    utils::prompt,
+    repository::{
+        downloader::{
+            cache::{self, PruneOptions},
+            keyring::{KeySource, Keyring},
+            schedule::ReleasePhase,
+        },
+        node_path::NodePath,
+        InstallSource, NodeVersion, Repository,
+    },
+    utils::{format_age, prompt, unix_now},
     version_detection::{self, VersionDetector},
+    versioning::{SimpleVersion, VersionMetadata},
 };
 use crossterm::style::Stylize;
 use dialoguer::{theme::ColorfulTheme, Input, Select};
-use miette::{Context, IntoDiagnostic, Result};
-use std::{ffi::OsString, str::FromStr};
+use indicatif::HumanBytes;
+use miette::{miette, Context, IntoDiagnostic, Result};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    ffi::OsString,
+    io::{self, IsTerminal},
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
 use tokio::fs;
 
+/// How often the same warning about an unsupported version is shown
+const NOTICE_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24);
+
 pub struct Nenv {
     config: ConfigAccess,
     repo: Repository,
     active_version: NodeVersion,
+    offline: bool,
 }
 
 impl Nenv {
     #[tracing::instrument(level = "debug")]
-    pub async fn init(version_override: Option<NodeVersion>) -> Result<Self> {
+    pub async fn init(version_override: Option<NodeVersion>, offline: bool) -> Result<Self> {
         let config = ConfigAccess::load().await?;
-        let repo = Repository::init(config.clone()).await?;
+        let repo = Repository::init(config.clone(), offline).await?;
         let default_version = { config.get().await.node.default_version.to_owned() };
 
         let active_version = if let Some(version) = version_override {
             version
         } else {
-            Self::get_active_version().await.unwrap_or(default_version)
+            Self::get_active_version().await?.unwrap_or(default_version)
         };
 
         Ok(Self {
             config,
             repo,
             active_version,
+            offline,
         })
     }
 
-    /// Installs the given node version.
-    /// Prompts if that version already exists
+    /// Installs the given node versions concurrently followed by the given archives.
+    /// Prompts for each version that already exists
     #[tracing::instrument(skip(self))]
-    pub async fn install(&mut self, version: NodeVersion) -> Result<()> {
-        Self::clear_version_cache().await?;
+    pub async fn install(&mut self, sources: Vec<InstallSource>) -> Result<()> {
+        let mut versions = Vec::new();
+        let mut archives = Vec::new();
+
+        for source in sources {
+            match source {
+                InstallSource::Version(version) => versions.push(version),
+                InstallSource::Archive(archive) => archives.push(archive),
+            }
+        }
 
-        if self.repo.is_installed(&version).await?
-            && !prompt(
-                false,
-                format!(
-                    "The version {} is already installed. Reinstall?",
-                    version.to_string().bold()
-                ),
-            )
-        {
+        if !self.offline {
+            for version in &versions {
+                if self.repo.lookup_remote_version(version).await.is_err() {
+                    // the version might have been released after the list was cached
+                    self.repo.refresh_versions().await?;
+                    break;
+                }
+            }
+        }
+        let mut to_install: Vec<(NodeVersion, VersionMetadata)> = Vec::new();
+
+        for version in versions {
+            let info = self.repo.lookup_remote_version(&version).await?.to_owned();
+
+            if to_install.iter().any(|(_, i)| i.version == info.version) {
+                continue;
+            }
+            if self.repo.is_build_installed(&info.version)
+                && !prompt(
+                    false,
+                    format!(
+                        "The version {} is already installed. Reinstall?",
+                        info.version.to_string().bold()
+                    ),
+                )
+            {
+                continue;
+            }
+            to_install.push((version, info));
+        }
+
+        if to_install.is_empty() && archives.is_empty() {
             println!("Nothing changed.");
-            Ok(())
-        } else {
-            self.repo.install_version(&version).await?;
-            self.active_version = version.to_owned();
+            return Ok(());
+        }
+        let (requested, infos): (Vec<_>, Vec<_>) = to_install.into_iter().unzip();
+        let schedule = self.repo.release_schedule().await;
+        let results = self.repo.install_versions(infos).await?;
+        let total = results.len() + archives.len();
+        let mut failed = 0;
+
+        for (info, result) in &results {
+            match result {
+                Ok(_) => println!(
+                    "{} Installed {}{}",
+                    "✓".green(),
+                    info.version.to_string().bold(),
+                    phase_tag(schedule.phase(&info.version))
+                ),
+                Err(e) => {
+                    failed += 1;
+                    println!(
+                        "{} Failed to install {}: {}",
+                        "✗".red(),
+                        info.version.to_string().bold(),
+                        e.root_cause()
+                    );
+                }
+            }
+        }
+        let mut last_installed = requested
+            .into_iter()
+            .zip(&results)
+            .filter(|(_, (_, res))| res.is_ok())
+            .map(|(version, _)| version)
+            .next_back();
+
+        for archive in archives {
+            match self.repo.install_archive(&archive).await {
+                Ok((info, flavor)) if flavor != self.repo.flavor() => {
+                    println!(
+                        "{} Installed {} [{}] from {archive}",
+                        "✓".green(),
+                        info.version.to_string().bold(),
+                        flavor.to_string().magenta()
+                    );
+                    eprintln!(
+                        "{} The build is only used when `download.flavor` is set to `{flavor}`.",
+                        "Warning:".yellow().bold()
+                    );
+                }
+                Ok((info, _)) => {
+                    println!(
+                        "{} Installed {} from {archive}",
+                        "✓".green(),
+                        info.version.to_string().bold()
+                    );
+                    last_installed = NodeVersion::from_str(&info.version.to_string()).ok();
+                }
+                Err(e) => {
+                    failed += 1;
+                    println!(
+                        "{} Failed to install {}: {}",
+                        "✗".red(),
+                        archive.bold(),
+                        e.root_cause()
+                    );
+                }
+            }
+        }
+
+        if let Some(version) = last_installed {
+            self.active_version = version;
             let mapper = self.get_mapper().await?;
             mapper.remap_additive().await?;
             mapper
                 .map_bins(self.get_binaries_with_path().await?)
                 .await?;
+        }
 
-            println!("Installed {}", version.to_string().bold());
+        if failed > 0 {
+            Err(miette!("Failed to install {failed} of {total} versions."))
+        } else {
             Ok(())
         }
     }
@@ -124,13 +248,41 @@ impl Nenv {
         }
         if !self.repo.is_installed(&self.active_version).await? {
             self.repo.install_version(&self.active_version).await?;
+        } else if self.config.get().await.node.latest_policy == LatestPolicy::Published {
+            self.offer_update().await?;
+        }
+        if let Err(e) = self.warn_unsupported_version().await {
+            tracing::debug!("Failed to check if the version is still supported: {e}");
         }
         let exit_status = self.get_mapper().await?.exec(command, args).await?;
 
         Ok(exit_status.code().unwrap_or(0))
     }
 
-    /// Clears the version cache and remaps all executables
+    /// Refreshes the list of available versions and remaps all executables
+    #[tracing::instrument(skip(self))]
+    pub async fn refresh(&mut self) -> Result<()> {
+        if self.repo.refresh_versions().await? {
+            println!("Updated the list of available versions.");
+
+            if let Some(latest) = self.repo.all_versions().await?.latest() {
+                println!(
+                    "The latest version is {}.",
+                    latest.version.to_string().bold()
+                );
+            }
+        } else {
+            println!("The list of available versions is up to date.");
+        }
+
+        if self.repo.is_installed(&self.active_version).await? {
+            self.remap().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Remaps all executables
     #[tracing::instrument(skip(self))]
     pub async fn remap(&mut self) -> Result<()> {
         let mapper = self.get_mapper().await?;
@@ -141,29 +293,37 @@ impl Nenv {
     /// Lists the currently installed versions
     #[tracing::instrument(skip(self))]
     pub async fn list_versions(&mut self) -> Result<()> {
-        let versions = self.repo.installed_versions();
         let active_version = self
             .repo
-            .lookup_remote_version(&self.active_version)
-            .await?;
-        let active_version = active_version.version.into();
+            .lookup_version(&self.active_version)
+            .await
+            .ok()
+            .map(|v| v.version);
 
+        let schedule = self.repo.cached_release_schedule().await;
         println!("{}", "Installed versions:".bold());
 
-        for version in versions {
-            let info = self
-                .repo
-                .all_versions()
-                .await?
-                .get(&version)
-                .ok_or_else(|| VersionError::unknown_version(version.to_string()))?;
+        for (flavor, info) in self.repo.installed_versions() {
+            let version = &info.version;
             let lts = info
                 .lts
                 .as_ref()
                 .map(|l| format!(" ({})", l.to_owned().green()))
                 .unwrap_or_default();
+            let channel = version.channel();
+            let lts = if channel.is_release() {
+                lts
+            } else {
+                format!("{lts} [{}]", channel.to_string().cyan())
+            };
+            let lts = if flavor.is_official() {
+                lts
+            } else {
+                format!("{lts} [{}]", flavor.to_string().magenta())
+            };
+            let lts = format!("{lts}{}", phase_tag(schedule.phase(version)));
 
-            if version == active_version {
+            if Some(version) == active_version.as_ref() && flavor == self.repo.flavor() {
                 println!(" {}{} [current]", version.to_string().blue().bold(), lts)
             } else {
                 println!(" {}{}", version.to_string().blue(), lts)
@@ -173,6 +333,148 @@ impl Nenv {
         Ok(())
     }
 
+    /// Reports installed, default and pinned versions that have
+    /// a newer security release in the same major version
+    #[tracing::instrument(skip(self))]
+    pub async fn audit(&mut self) -> Result<()> {
+        let mut audited: BTreeMap<SimpleVersion, Vec<String>> = BTreeMap::new();
+
+        for (_, info) in self.repo.installed_versions() {
+            audited
+                .entry(info.version.to_owned())
+                .or_default()
+                .push(String::from("installed"));
+        }
+        let default_version = self.config.get().await.node.default_version.to_owned();
+        let mut requested = vec![(String::from("default"), default_version)];
+        requested.extend(
+            self.config
+                .get()
+                .await
+                .bins
+                .iter()
+                .map(|(bin, cfg)| (format!("pinned by {bin}"), cfg.node_version.to_owned())),
+        );
+
+        for (source, version) in requested {
+            match self.repo.lookup_version(&version).await {
+                Ok(info) => audited.entry(info.version).or_default().push(source),
+                Err(e) => eprintln!(
+                    "{} Skipping the {source} version {version}: {}",
+                    "Warning:".yellow().bold(),
+                    e.root_cause()
+                ),
+            }
+        }
+        let versions = self.repo.all_versions().await?;
+        let mut vulnerable = 0;
+
+        for (version, sources) in &audited {
+            let sources = sources.join(", ");
+            let update = if version.channel().is_release() {
+                versions.security_update(version)
+            } else {
+                None
+            };
+
+            if let Some(update) = update {
+                vulnerable += 1;
+                println!(
+                    "{} {} ({sources}) has the newer security release {}",
+                    "✗".red(),
+                    version.to_string().bold(),
+                    update.version.to_string().bold()
+                );
+            } else {
+                println!("{} {} ({sources})", "✓".green(), version.to_string().bold());
+            }
+        }
+
+        if vulnerable > 0 {
+            Err(miette!(
+                "{vulnerable} of {} versions have newer security releases.",
+                audited.len()
+            ))
+        } else {
+            println!("No security releases are missing.");
+            Ok(())
+        }
+    }
+
+    /// Lists the published versions that match the given filters
+    #[tracing::instrument(skip(self))]
+    pub async fn ls_remote(&mut self, args: LsRemoteArgs) -> Result<()> {
+        let flavor = self.repo.flavor();
+        let installed = self
+            .repo
+            .installed_versions()
+            .into_iter()
+            .filter(|(f, _)| *f == flavor)
+            .map(|(_, v)| v.version.to_owned())
+            .collect::<HashSet<_>>();
+        let lts_name = args.lts_name.map(|n| n.to_lowercase());
+        let versions = self.repo.all_versions().await?;
+
+        let matching = versions
+            .all()
+            .filter(|v| !args.lts || v.lts.is_some())
+            .filter(|v| {
+                lts_name
+                    .as_ref()
+                    .is_none_or(|name| v.lts.as_ref().is_some_and(|l| l.to_lowercase() == *name))
+            })
+            .filter(|v| args.major.is_none_or(|major| v.version.major == major))
+            .filter(|v| !args.security_only || v.security)
+            .filter(|v| {
+                args.since
+                    .as_ref()
+                    .is_none_or(|since| v.date.as_ref().is_some_and(|date| date >= since))
+            })
+            .filter(|v| !args.installable || versions.has_platform_build(v))
+            .collect::<Vec<_>>();
+
+        if matching.is_empty() {
+            println!("No versions match the given filters.");
+            return Ok(());
+        }
+
+        for info in matching {
+            let version = format!("{:<10}", info.version.to_string());
+            let date = info.date.as_deref().unwrap_or("-");
+            let lts = info
+                .lts
+                .as_ref()
+                .map(|l| format!(" ({})", l.to_owned().green()))
+                .unwrap_or_default();
+            let npm = info
+                .npm
+                .as_ref()
+                .map(|npm| format!(" npm {npm}"))
+                .unwrap_or_default();
+            let v8 = info
+                .v8
+                .as_ref()
+                .map(|v8| format!(" v8 {v8}"))
+                .unwrap_or_default();
+            let security = if info.security {
+                format!(" [{}]", "security".red())
+            } else {
+                String::new()
+            };
+
+            if installed.contains(&info.version) {
+                println!(
+                    " {} {date}{lts}{npm}{v8}{security} [installed]",
+                    version.blue().bold()
+                )
+            } else {
+                println!(" {} {date}{lts}{npm}{v8}{security}", version.blue())
+            }
+        }
+
+        Ok(())
+    }
+
     /// Initializes nenv and prompts for a default version.
     #[tracing::instrument(skip(self))]
     pub async fn init_nenv(&mut self) -> Result<()> {
@@ -209,18 +511,128 @@ impl Nenv {
         Ok(())
     }
 
-    /// Clears the download cache
+    /// Packs the archives of the given versions into a bundle
     #[tracing::instrument(skip(self))]
-    pub async fn clear_cache(&self) -> Result<()> {
-        fs::remove_dir_all(&*CACHE_DIR)
-            .await
-            .into_diagnostic()
-            .context("Removing cache directory")?;
+    pub async fn create_bundle(
+        &mut self,
+        versions: Vec<NodeVersion>,
+        output: PathBuf,
+    ) -> Result<()> {
+        let manifest = self.repo.create_bundle(&versions, &output).await?;
+
+        for release in &manifest.releases {
+            println!(
+                "{} Bundled {}",
+                "✓".green(),
+                release.version.to_string().bold()
+            );
+        }
+        println!(
+            "Created bundle {} ({}).",
+            output.display().to_string().bold(),
+            HumanBytes(manifest.files.iter().map(|f| f.size).sum())
+        );
+
+        Ok(())
+    }
+
+    /// Imports a bundle and optionally installs the bundled versions
+    #[tracing::instrument(skip(self))]
+    pub async fn import_bundle(&mut self, path: PathBuf, install: bool) -> Result<()> {
+        let infos = self.repo.import_bundle(&path).await?;
+
+        for info in &infos {
+            println!(
+                "{} Imported {}",
+                "✓".green(),
+                info.version.to_string().bold()
+            );
+        }
+
+        if install {
+            let sources = infos
+                .iter()
+                .map(|i| InstallSource::Version(NodeVersion::exact(&i.version)))
+                .collect();
+            self.install(sources).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Clears the download cache.
+    /// The cached lists of versions are only removed when requested.
+    #[tracing::instrument(skip(self))]
+    pub async fn clear_cache(&self, versions: bool) -> Result<()> {
+        let _lock = FileLock::exclusive("cache", "using the download cache").await?;
+        let removed = cache::clear(versions).await?;
         fs::create_dir_all(&*CACHE_DIR)
             .await
             .into_diagnostic()
             .context("Creating cache directory")?;
-        println!("Cleared download cache.");
+        println!(
+            "Cleared download cache ({} freed).",
+            HumanBytes(removed.iter().map(|e| e.size).sum())
+        );
+
+        Ok(())
+    }
+
+    /// Lists all files in the download cache
+    #[tracing::instrument(skip(self))]
+    pub async fn list_cache(&self) -> Result<()> {
+        let entries = cache::entries().await?;
+        let name_width = entries.iter().map(|e| e.name.len()).max().unwrap_or(0);
+
+        println!("{}", "Download cache:".bold());
+
+        for entry in &entries {
+            println!(
+                " {} {:>10}  {} ago  {}",
+                format!("{:name_width$}", entry.name).blue(),
+                HumanBytes(entry.size).to_string(),
+                format_age(entry.age()),
+                entry.kind.name().dim()
+            );
+        }
+        println!(
+            "{} {} in {} files",
+            "Total:".bold(),
+            HumanBytes(entries.iter().map(|e| e.size).sum()),
+            entries.len()
+        );
+
+        Ok(())
+    }
+
+    /// Removes cached archives that haven't been used for the given time.
+    /// The archives of installed versions can be kept so they can be reinstalled offline.
+    #[tracing::instrument(skip(self))]
+    pub async fn prune_cache(
+        &self,
+        older_than: Option<Duration>,
+        keep_installed: bool,
+    ) -> Result<()> {
+        let keep = if keep_installed {
+            self.repo
+                .installed_versions()
+                .into_iter()
+                .flat_map(|(flavor, info)| cache::file_names(&info.version, flavor))
+                .collect()
+        } else {
+            HashSet::new()
+        };
+        let _lock = FileLock::exclusive("cache", "using the download cache").await?;
+        let removed = cache::prune(&PruneOptions { older_than, keep }).await?;
+
+        for entry in &removed {
+            println!(" {} {}", "-".red().bold(), entry.name);
+        }
+        println!(
+            "Removed {} files ({} freed).",
+            removed.len(),
+            HumanBytes(removed.iter().map(|e| e.size).sum())
+        );
 
         Ok(())
     }
@@ -254,6 +666,141 @@ impl Nenv {
         Ok(())
     }
 
+    /// Creates or replaces an alias for a version
+    #[tracing::instrument(skip(self))]
+    pub async fn set_alias(&mut self, name: String, version: NodeVersion) -> Result<()> {
+        let name = name.to_lowercase();
+        let mut aliases = self.repo.aliases().to_owned();
+        aliases.insert(&name, version.to_owned())?;
+
+        self.config
+            .get_mut()
+            .await
+            .aliases
+            .insert(name.to_owned(), version.to_owned());
+        println!(
+            "Aliased {} to {}",
+            name.bold(),
+            version.to_string().yellow().bold()
+        );
+
+        Ok(())
+    }
+
+    /// Removes an alias
+    #[tracing::instrument(skip(self))]
+    pub async fn remove_alias(&mut self, name: String) -> Result<()> {
+        let name = name.to_lowercase();
+
+        if self.repo.aliases().get(&name).is_none() {
+            return Err(AliasError::Unknown { name }.into());
+        }
+        let mut config = self.config.get_mut().await;
+        config.aliases.retain(|n, _| n.to_lowercase() != name);
+        println!("Removed the alias {}", name.bold());
+
+        Ok(())
+    }
+
+    /// Lists all aliases with the installed versions they resolve to
+    #[tracing::instrument(skip(self))]
+    pub async fn list_aliases(&mut self) -> Result<()> {
+        let aliases = self.repo.aliases();
+
+        if aliases.all().is_empty() {
+            println!("No aliases are configured.");
+            return Ok(());
+        }
+        println!("{}", "Aliases:".bold());
+
+        for (name, version) in aliases.all() {
+            let resolved = match aliases.resolve(&NodeVersion::Lts(name.to_owned())) {
+                Ok(resolved) => match self.repo.lookup_local_version(&resolved) {
+                    Ok(info) => format!(" ({})", info.version.to_string().blue()),
+                    Err(_) => String::from(" [not installed]"),
+                },
+                Err(e) => format!(" [{}]", e.to_string().red()),
+            };
+            println!(
+                " {} -> {}{resolved}",
+                name.as_str().bold(),
+                version.to_string().yellow()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Imports release signing keys from the given file
+    #[tracing::instrument(skip(self))]
+    pub async fn import_keys(&self, path: PathBuf) -> Result<()> {
+        let keys = Keyring::import(&path).await?;
+
+        for key in keys {
+            println!(
+                "Imported {} {}",
+                key.fingerprint().yellow().bold(),
+                key.user_ids().join(", ")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Lists all known release signing keys
+    #[tracing::instrument(skip(self))]
+    pub async fn list_keys(&self) -> Result<()> {
+        let keyring = Keyring::load().await?;
+
+        println!("{}", "Release keys:".bold());
+
+        for key in keyring.keys() {
+            let source = match key.source() {
+                KeySource::Bundled => "bundled",
+                KeySource::Imported => "imported",
+            };
+            println!(
+                " {} [{}] {}",
+                key.fingerprint().blue(),
+                source,
+                key.user_ids().join(", ")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Removes an imported release signing key
+    #[tracing::instrument(skip(self))]
+    pub async fn remove_key(&self, fingerprint: String) -> Result<()> {
+        let key = Keyring::load().await?.remove(&fingerprint).await?;
+        println!("Removed {}", key.fingerprint().bold());
+
+        Ok(())
+    }
+
+    /// Tests the reachability of all configured mirrors
+    #[tracing::instrument(skip(self))]
+    pub async fn test_mirrors(&self) -> Result<()> {
+        let results = self.repo.downloader().test_mirrors().await;
+
+        println!("{}", "Mirrors:".bold());
+
+        for (mirror, result) in results {
+            match result {
+                Ok(duration) => println!(
+                    " {} {} ({} ms)",
+                    "✓".green().bold(),
+                    mirror.url().blue(),
+                    duration.as_millis()
+                ),
+                Err(e) => println!(" {} {} ({e})", "✗".red().bold(), mirror.url().blue()),
+            }
+        }
+
+        Ok(())
+    }
+
     /// Persits all changes made that aren't written to the disk yet
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn persist(&self) -> Result<()> {
@@ -261,19 +808,72 @@ impl Nenv {
     }
 
     #[tracing::instrument(level = "debug")]
-    async fn get_active_version() -> Option<NodeVersion> {
-        version_detection::ParallelDetector::detect_version()
-            .await
-            .ok()
-            .and_then(|v| v)
+    async fn get_active_version() -> Result<Option<NodeVersion>> {
+        version_detection::ParallelDetector::detect_version().await
     }
 
-    #[tracing::instrument(level = "debug")]
-    async fn clear_version_cache() -> Result<()> {
-        if VERSION_FILE_PATH.exists() {
-            fs::remove_file(&*VERSION_FILE_PATH)
-                .await
-                .into_diagnostic()?;
+    /// Prompts to install the newest published version when `latest` or `lts`
+    /// resolves to an older installed version.
+    /// The prompt is only shown when running in a terminal and at most once a day for each version.
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn offer_update(&mut self) -> Result<()> {
+        let Some(published) = self.repo.published_update(&self.active_version).await else {
+            return Ok(());
+        };
+
+        if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+            tracing::debug!("Node v{} has been published", published.version);
+            return Ok(());
+        }
+        // a declined update isn't offered again until the notice interval has passed
+        if !notice_due(format!("update-{}", published.version)).await? {
+            return Ok(());
+        }
+        if prompt(
+            false,
+            format!(
+                "Node {} has been published. Do you want to install it?",
+                published.version.to_string().bold()
+            ),
+        ) {
+            self.repo
+                .install_version(&NodeVersion::exact(&published.version))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Warns when the active version has reached its end of life
+    /// or has a newer security release. Each warning is only shown once a day.
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn warn_unsupported_version(&mut self) -> Result<()> {
+        let version = self
+            .repo
+            .lookup_local_version(&self.active_version)?
+            .version
+            .to_owned();
+        let schedule = self.repo.cached_release_schedule().await;
+
+        if let Some(end) = schedule.end_of_life(&version) {
+            show_notice(
+                format!("eol-{}", version.major),
+                format!(
+                    "Node {} has reached its end of life on {end} and no longer receives security updates.",
+                    version.major
+                ),
+            )
+            .await?;
+        }
+        if let Some(update) = self.repo.cached_security_update(&version).await {
+            show_notice(
+                format!("security-{version}"),
+                format!(
+                    "Node {version} has the newer security release {}. Run `nenv audit` for details.",
+                    update.version
+                ),
+            )
+            .await?;
         }
 
         Ok(())
@@ -304,3 +904,54 @@ impl Nenv {
         Ok(binaries_with_path)
     }
 }
+
+/// Returns the colored phase of a version that is appended to its version number
+fn phase_tag(phase: Option<ReleasePhase>) -> String {
+    let Some(phase) = phase else {
+        return String::new();
+    };
+    let name = phase.to_string();
+    let name = match phase {
+        ReleasePhase::Current => name.cyan(),
+        ReleasePhase::ActiveLts => name.green(),
+        ReleasePhase::Maintenance => name.yellow(),
+        ReleasePhase::EndOfLife => name.red().bold(),
+    };
+
+    format!(" [{name}]")
+}
+
+/// Prints a warning unless the notice with the given key
+/// has already been shown within the notice interval
+async fn show_notice(key: String, message: String) -> Result<()> {
+    if notice_due(key).await? {
+        eprintln!("{} {message}", "Warning:".yellow().bold());
+    }
+
+    Ok(())
+}
+
+/// Returns if the notice with the given key hasn't been shown within
+/// the notice interval and records it as shown
+async fn notice_due(key: String) -> Result<bool> {
+    let mut notices: HashMap<String, u64> = fs::read(&*NOTICES_FILE)
+        .await
+        .ok()
+        .and_then(|c| serde_json::from_slice(&c).ok())
+        .unwrap_or_default();
+    let now = unix_now();
+
+    if notices
+        .get(&key)
+        .is_some_and(|t| now.saturating_sub(*t) < NOTICE_INTERVAL.as_secs())
+    {
+        return Ok(false);
+    }
+    notices.insert(key, now);
+    write_atomic(
+        &NOTICES_FILE,
+        &serde_json::to_vec(&notices).into_diagnostic()?,
+    )?;
+
+    Ok(true)
+}
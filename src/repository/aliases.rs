@@ -0,0 +1,212 @@
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+use crate::error::AliasError;
+
+use super::NodeVersion;
+
+/// User defined names for node versions
+#[derive(Clone, Debug, Default)]
+pub struct Aliases {
+    aliases: HashMap<String, NodeVersion>,
+    /// The lowercase codenames of lts lines that can't be used as alias names
+    codenames: HashSet<String>,
+}
+
+impl Aliases {
+    pub fn new<I: IntoIterator<Item = String>>(
+        aliases: &HashMap<String, NodeVersion>,
+        codenames: I,
+    ) -> Self {
+        Self {
+            aliases: aliases
+                .iter()
+                .map(|(name, version)| (name.to_lowercase(), version.to_owned()))
+                .collect(),
+            codenames: codenames.into_iter().map(|c| c.to_lowercase()).collect(),
+        }
+    }
+
+    /// Returns all aliases ordered by name
+    pub fn all(&self) -> Vec<(&String, &NodeVersion)> {
+        let mut aliases = self.aliases.iter().collect::<Vec<_>>();
+        aliases.sort_by_key(|(name, _)| *name);
+
+        aliases
+    }
+
+    /// Adds or replaces an alias after checking that its name is valid
+    /// and that it doesn't create a cycle
+    pub fn insert(&mut self, name: &str, version: NodeVersion) -> Result<(), AliasError> {
+        let name = name.to_lowercase();
+
+        match NodeVersion::from_str(&name) {
+            Ok(NodeVersion::Lts(parsed)) if parsed == name => {}
+            _ => return Err(AliasError::Reserved { name }),
+        }
+        if self.codenames.contains(&name) {
+            return Err(AliasError::ShadowsCodename { name });
+        }
+        self.aliases.insert(name.to_owned(), version);
+        self.resolve(&NodeVersion::Lts(name))?;
+
+        Ok(())
+    }
+
+    /// Returns the version the alias with the given name refers to
+    pub fn get(&self, name: &str) -> Option<&NodeVersion> {
+        self.aliases.get(&name.to_lowercase())
+    }
+
+    /// Follows aliases until a version is reached that isn't an alias.
+    /// Versions that aren't aliases are returned unchanged.
+    pub fn resolve(&self, version: &NodeVersion) -> Result<NodeVersion, AliasError> {
+        let mut chain: Vec<&str> = Vec::new();
+        let mut current = version;
+
+        while let NodeVersion::Lts(name) = current {
+            let Some(target) = self.aliases.get(name) else {
+                break;
+            };
+            if self.codenames.contains(name) {
+                return Err(AliasError::ShadowsCodename {
+                    name: name.to_owned(),
+                });
+            }
+            if chain.contains(&name.as_str()) {
+                chain.push(name);
+
+                return Err(AliasError::Cycle {
+                    name: chain[0].to_owned(),
+                    chain: chain.join(" -> "),
+                });
+            }
+            chain.push(name);
+            current = target;
+        }
+
+        Ok(current.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(s: &str) -> NodeVersion {
+        NodeVersion::from_str(s).unwrap()
+    }
+
+    fn aliases(aliases: &[(&str, &str)]) -> Aliases {
+        let aliases = aliases
+            .iter()
+            .map(|(name, v)| (name.to_string(), version(v)))
+            .collect::<HashMap<_, _>>();
+
+        Aliases::new(&aliases, vec![String::from("Iron")])
+    }
+
+    #[test]
+    fn it_resolves_chains_of_aliases() {
+        let aliases = aliases(&[("work", "project"), ("project", "20.11")]);
+
+        assert!(matches!(
+            aliases.resolve(&version("work")).unwrap(),
+            NodeVersion::Req(_)
+        ));
+        assert!(matches!(
+            aliases.resolve(&version("Project")).unwrap(),
+            NodeVersion::Req(_)
+        ));
+    }
+
+    #[test]
+    fn it_returns_versions_that_arent_aliases_unchanged() {
+        let aliases = aliases(&[("work", "20")]);
+
+        assert!(matches!(
+            aliases.resolve(&version("latest")).unwrap(),
+            NodeVersion::Latest
+        ));
+        assert!(matches!(
+            aliases.resolve(&version("lts")).unwrap(),
+            NodeVersion::LatestLts
+        ));
+        let NodeVersion::Lts(name) = aliases.resolve(&version("iron")).unwrap() else {
+            panic!("expected the codename to be returned");
+        };
+        assert_eq!(name, "iron");
+    }
+
+    #[test]
+    fn it_detects_cycles() {
+        let aliases = aliases(&[("a", "b"), ("b", "a")]);
+
+        let Err(AliasError::Cycle { name, chain }) = aliases.resolve(&version("a")) else {
+            panic!("expected a cycle");
+        };
+        assert_eq!(name, "a");
+        assert_eq!(chain, "a -> b -> a");
+    }
+
+    #[test]
+    fn it_detects_aliases_that_refer_to_themselves() {
+        let aliases = aliases(&[("work", "work")]);
+
+        let Err(AliasError::Cycle { chain, .. }) = aliases.resolve(&version("work")) else {
+            panic!("expected a cycle");
+        };
+        assert_eq!(chain, "work -> work");
+    }
+
+    #[test]
+    fn it_refuses_to_insert_cycles() {
+        let mut aliases = aliases(&[("a", "b")]);
+
+        assert!(matches!(
+            aliases.insert("b", version("a")),
+            Err(AliasError::Cycle { .. })
+        ));
+        assert!(matches!(
+            aliases.insert("c", version("c")),
+            Err(AliasError::Cycle { .. })
+        ));
+        assert!(aliases.insert("b", version("20")).is_ok());
+    }
+
+    #[test]
+    fn it_refuses_names_of_versions() {
+        let mut aliases = Aliases::default();
+
+        for name in ["lts", "LTS", "latest", "20", "^20.1", "nightly"] {
+            assert!(
+                matches!(
+                    aliases.insert(name, version("20")),
+                    Err(AliasError::Reserved { .. })
+                ),
+                "{name} was accepted as an alias name"
+            );
+        }
+        assert!(aliases.all().is_empty());
+    }
+
+    #[test]
+    fn it_doesnt_shadow_codenames() {
+        let mut empty = aliases(&[]);
+
+        assert!(matches!(
+            empty.insert("Iron", version("18")),
+            Err(AliasError::ShadowsCodename { .. })
+        ));
+
+        // aliases that were configured before the codename was published
+        let configured = aliases(&[("iron", "18"), ("work", "iron")]);
+
+        assert!(matches!(
+            configured.resolve(&version("work")),
+            Err(AliasError::ShadowsCodename { name }) if name == "iron"
+        ));
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use miette::{Context, IntoDiagnostic, Result};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::{
+    config::Flavor,
+    consts::{ARCH, OS},
+    error::BundleError,
+    versioning::{Channel, SimpleVersion},
+};
+
+use super::downloader::checksums::{file_checksum, ChecksumHasher};
+
+/// The version of the bundle layout that is written
+const BUNDLE_FORMAT: u32 = 1;
+const MANIFEST_NAME: &str = "manifest.json";
+/// The directory in the bundle with the files that are copied into the download cache
+const CACHE_DIR_NAME: &str = "cache";
+
+/// Describes the contents of a bundle so that its integrity
+/// can be checked before it is imported
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// The version of the bundle layout
+    pub format: u32,
+    /// The platform the bundled builds are for like `linux-x64`
+    pub platform: String,
+    pub flavor: Flavor,
+    pub releases: Vec<BundleRelease>,
+    /// All files in the bundle except the manifest
+    pub files: Vec<BundleFile>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleRelease {
+    pub version: SimpleVersion,
+    /// The file name of the archive of the release
+    pub archive: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleFile {
+    /// The path of the file inside the bundle
+    pub name: String,
+    pub size: u64,
+    /// The hex encoded sha256 of the file
+    pub sha256: String,
+}
+
+/// The content of a file that is added to a bundle
+#[derive(Clone, Debug)]
+pub enum BundleContent {
+    File(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+impl BundleManifest {
+    pub fn new(flavor: Flavor, releases: Vec<BundleRelease>) -> Self {
+        Self {
+            format: BUNDLE_FORMAT,
+            platform: format!("{OS}-{ARCH}"),
+            flavor,
+            releases,
+            files: Vec::new(),
+        }
+    }
+
+    /// Returns the files that are copied into the download cache
+    /// with the name they are cached as
+    pub fn cache_files(&self) -> impl Iterator<Item = (&BundleFile, &str)> {
+        self.files.iter().filter_map(|f| {
+            let name = f.name.strip_prefix(CACHE_DIR_NAME)?.strip_prefix('/')?;
+            Some((f, name))
+        })
+    }
+
+    /// Checks that the bundled builds can be used with the given flavor on this platform
+    pub fn check_platform(&self, flavor: Flavor) -> Result<(), BundleError> {
+        let platform = format!("{OS}-{ARCH}");
+
+        if self.platform != platform || self.flavor != flavor {
+            Err(BundleError::PlatformMismatch {
+                bundled: format!("{} ({})", self.platform, self.flavor),
+                current: format!("{platform} ({flavor})"),
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Returns the name of the bundled index of the versions in the given channel
+pub fn index_file_name(channel: Channel) -> String {
+    if channel.is_release() {
+        String::from("index.json")
+    } else {
+        format!("index-{channel}.json")
+    }
+}
+
+/// Returns the name of a bundled file that is copied into the download cache
+pub fn cache_file_name(name: &str) -> String {
+    format!("{CACHE_DIR_NAME}/{name}")
+}
+
+/// Writes a bundle with the given contents and a manifest listing their checksums.
+/// The bundle is only moved to the given path once it is complete.
+#[tracing::instrument(level = "debug", skip(manifest, contents))]
+pub async fn write(
+    path: &Path,
+    mut manifest: BundleManifest,
+    contents: Vec<(String, BundleContent)>,
+) -> Result<BundleManifest> {
+    for (name, content) in &contents {
+        let (size, sha256) = match content {
+            BundleContent::File(path) => (
+                fs::metadata(path).await.into_diagnostic()?.len(),
+                file_checksum(path).await?,
+            ),
+            BundleContent::Bytes(bytes) => {
+                let mut hasher = ChecksumHasher::default();
+                hasher.update(bytes);
+                (bytes.len() as u64, hasher.finish())
+            }
+        };
+        manifest.files.push(BundleFile {
+            name: name.to_owned(),
+            size,
+            sha256,
+        });
+    }
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest).into_diagnostic()?;
+    let part_path = PathBuf::from(format!("{}.part", path.display()));
+    let tar_path = part_path.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let mut builder = tar::Builder::new(File::create(&tar_path)?);
+        append_bytes(&mut builder, MANIFEST_NAME, &manifest_bytes)?;
+
+        for (name, content) in contents {
+            match content {
+                BundleContent::File(path) => builder.append_path_with_name(path, name)?,
+                BundleContent::Bytes(bytes) => append_bytes(&mut builder, &name, &bytes)?,
+            }
+        }
+        builder.into_inner()?.sync_all()
+    })
+    .await
+    .into_diagnostic()?
+    .into_diagnostic()
+    .with_context(|| format!("Writing bundle {path:?}"))?;
+
+    fs::rename(&part_path, path)
+        .await
+        .into_diagnostic()
+        .with_context(|| format!("Moving bundle to {path:?}"))?;
+
+    Ok(manifest)
+}
+
+/// Extracts the bundle into the given directory and returns
+/// its manifest once all files have been verified
+#[tracing::instrument(level = "debug")]
+pub async fn extract(path: &Path, dst: &Path) -> Result<BundleManifest> {
+    let (bundle_path, dst_path) = (path.to_owned(), dst.to_owned());
+
+    tokio::task::spawn_blocking(move || {
+        tar::Archive::new(File::open(bundle_path)?).unpack(dst_path)
+    })
+    .await
+    .into_diagnostic()?
+    .into_diagnostic()
+    .with_context(|| format!("Extracting bundle {path:?}"))?;
+
+    let invalid_manifest = |reason: String| BundleError::InvalidManifest {
+        bundle: path.display().to_string(),
+        reason,
+    };
+    let manifest_path = dst.join(MANIFEST_NAME);
+
+    if !manifest_path.exists() {
+        return Err(invalid_manifest(String::from("it is missing")).into());
+    }
+    let contents = fs::read(&manifest_path).await.into_diagnostic()?;
+    let manifest: BundleManifest =
+        serde_json::from_slice(&contents).map_err(|e| invalid_manifest(e.to_string()))?;
+
+    if manifest.format > BUNDLE_FORMAT {
+        return Err(invalid_manifest(format!(
+            "it uses the unsupported format {}",
+            manifest.format
+        ))
+        .into());
+    }
+    verify(&manifest, dst).await?;
+
+    Ok(manifest)
+}
+
+/// Checks the size and checksum of every file listed in the manifest
+async fn verify(manifest: &BundleManifest, dir: &Path) -> Result<()> {
+    for file in &manifest.files {
+        let corrupted = |reason: &str| BundleError::Corrupted {
+            file: file.name.to_owned(),
+            reason: reason.to_owned(),
+        };
+        // files are only read from the bundle root and the cache directory
+        let valid_name = match file.name.split_once('/') {
+            Some((CACHE_DIR_NAME, name)) => is_plain_file_name(name),
+            Some(_) => false,
+            None => is_plain_file_name(&file.name),
+        };
+
+        if !valid_name {
+            return Err(corrupted("has an invalid name").into());
+        }
+        let path = dir.join(&file.name);
+
+        if !path.is_file() {
+            return Err(corrupted("is missing").into());
+        }
+        if fs::metadata(&path).await.into_diagnostic()?.len() != file.size
+            || file_checksum(&path).await? != file.sha256
+        {
+            return Err(corrupted("doesn't match the checksum in the manifest").into());
+        }
+    }
+
+    Ok(())
+}
+
+fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty() && name != ".." && !name.contains(['/', '\\'])
+}
+
+fn append_bytes<W: Write>(
+    builder: &mut tar::Builder<W>,
+    name: &str,
+    bytes: &[u8],
+) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+    );
+    builder.append_data(&mut header, name, bytes)
+}
@@ -0,0 +1,289 @@
+use std::{fmt, path::PathBuf};
+
+use reqwest::{RequestBuilder, Url};
+
+use crate::config::MirrorAuth;
+
+/// A secret value that is never printed
+#[derive(Clone)]
+pub struct Secret(String);
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+/// The resolved credentials for a mirror
+#[derive(Clone, Debug)]
+pub enum Credentials {
+    Bearer(Secret),
+    Basic { username: String, password: Secret },
+}
+
+impl Credentials {
+    /// Resolves the credentials configured for the mirror with the given url
+    pub fn resolve(auth: &MirrorAuth, url: &str) -> Result<Self, String> {
+        match auth {
+            MirrorAuth::Bearer { token_env } => {
+                let token = read_env(token_env)?;
+                Ok(Self::Bearer(Secret(token)))
+            }
+            MirrorAuth::Basic {
+                username,
+                password_env,
+            } => {
+                let password = read_env(password_env)?;
+                Ok(Self::Basic {
+                    username: username.to_owned(),
+                    password: Secret(password),
+                })
+            }
+            MirrorAuth::Netrc => {
+                let host = Url::parse(url)
+                    .ok()
+                    .and_then(|u| u.host_str().map(String::from))
+                    .ok_or_else(|| format!("the url {url} has no host"))?;
+                read_netrc(&host)
+            }
+        }
+    }
+
+    /// Adds the credentials to the given request
+    pub fn apply(&self, req: RequestBuilder) -> RequestBuilder {
+        match self {
+            Credentials::Bearer(token) => req.bearer_auth(&token.0),
+            Credentials::Basic { username, password } => {
+                req.basic_auth(username, Some(&password.0))
+            }
+        }
+    }
+}
+
+fn read_env(name: &str) -> Result<String, String> {
+    std::env::var(name).map_err(|_| format!("the environment variable {name} is not set"))
+}
+
+/// Reads the credentials for the given host from the netrc file
+fn read_netrc(host: &str) -> Result<Credentials, String> {
+    let path = std::env::var_os("NETRC")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".netrc")))
+        .ok_or_else(|| String::from("no netrc file found"))?;
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("failed to read the netrc file {path:?}: {e}"))?;
+
+    parse_netrc(&contents, host)
+        .ok_or_else(|| format!("the netrc file {path:?} contains no entry for {host}"))
+}
+
+/// Parses the netrc file and returns the entry for the given host
+/// or the default entry
+fn parse_netrc(contents: &str, host: &str) -> Option<Credentials> {
+    let mut tokens = netrc_tokens(contents).into_iter();
+    let mut entries = Vec::new();
+    let mut current: Option<(Option<String>, Option<String>, Option<String>)> = None;
+
+    while let Some(token) = tokens.next() {
+        match token.as_str() {
+            "machine" => {
+                entries.extend(current.take());
+                current = Some((tokens.next(), None, None));
+            }
+            "default" => {
+                entries.extend(current.take());
+                current = Some((None, None, None));
+            }
+            "login" => {
+                if let Some(entry) = current.as_mut() {
+                    entry.1 = tokens.next();
+                }
+            }
+            "password" => {
+                if let Some(entry) = current.as_mut() {
+                    entry.2 = tokens.next();
+                }
+            }
+            _ => {}
+        }
+    }
+    entries.extend(current.take());
+
+    let (_, login, password) = entries
+        .iter()
+        .find(|(machine, _, _)| machine.as_deref() == Some(host))
+        .or_else(|| entries.iter().find(|(machine, _, _)| machine.is_none()))?;
+
+    Some(Credentials::Basic {
+        username: login.to_owned().unwrap_or_default(),
+        password: Secret(password.to_owned()?),
+    })
+}
+
+/// Splits the netrc file into its tokens.
+/// Macro definitions end with an empty line and are skipped
+/// as they are not relevant for authentication.
+fn netrc_tokens(contents: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut in_macro = false;
+
+    for line in contents.lines() {
+        if in_macro {
+            in_macro = !line.trim().is_empty();
+            continue;
+        }
+        let line_tokens = split_netrc_line(line);
+
+        match line_tokens.iter().position(|t| t == "macdef") {
+            Some(index) => {
+                tokens.extend(line_tokens.into_iter().take(index));
+                in_macro = true;
+            }
+            None => tokens.extend(line_tokens),
+        }
+    }
+
+    tokens
+}
+
+/// Splits a line of the netrc file at whitespace.
+/// Tokens in double quotes can contain whitespace and escaped characters.
+fn split_netrc_line(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut token = String::new();
+
+        if c == '"' {
+            chars.next();
+
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => token.extend(chars.next()),
+                    c => token.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                token.push(c);
+            }
+        }
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETRC: &str = "\
+machine example.com login alice password secret
+
+macdef init
+machine evil.com login mallory password stolen
+
+machine mirror.example.com
+    login bob
+    password \"correct horse \\\"battery\\\" staple\"
+
+default login anonymous password guest
+";
+
+    fn basic(credentials: Option<Credentials>) -> Option<(String, String)> {
+        match credentials? {
+            Credentials::Basic { username, password } => Some((username, password.0)),
+            Credentials::Bearer(_) => None,
+        }
+    }
+
+    #[test]
+    fn it_reads_the_entry_of_the_host() {
+        assert_eq!(
+            basic(parse_netrc(NETRC, "example.com")),
+            Some((String::from("alice"), String::from("secret")))
+        );
+    }
+
+    #[test]
+    fn it_reads_quoted_tokens_over_multiple_lines() {
+        assert_eq!(
+            basic(parse_netrc(NETRC, "mirror.example.com")),
+            Some((
+                String::from("bob"),
+                String::from("correct horse \"battery\" staple")
+            ))
+        );
+    }
+
+    #[test]
+    fn it_skips_macro_definitions() {
+        assert_eq!(
+            basic(parse_netrc(NETRC, "evil.com")),
+            Some((String::from("anonymous"), String::from("guest")))
+        );
+        assert_eq!(
+            basic(parse_netrc(
+                "machine example.com login alice macdef init\nlogin mallory\n\npassword secret",
+                "example.com"
+            )),
+            Some((String::from("alice"), String::from("secret")))
+        );
+    }
+
+    #[test]
+    fn it_falls_back_to_the_default_entry() {
+        assert_eq!(
+            basic(parse_netrc(NETRC, "unknown.com")),
+            Some((String::from("anonymous"), String::from("guest")))
+        );
+        assert!(parse_netrc(
+            "machine example.com login alice password secret",
+            "unknown.com"
+        )
+        .is_none());
+        assert!(parse_netrc("machine example.com login alice", "example.com").is_none());
+    }
+
+    #[test]
+    fn it_matches_hosts_of_urls_with_a_port() {
+        let path = std::env::temp_dir().join(format!("nenv-test-netrc-{}", std::process::id()));
+        std::fs::write(&path, NETRC).unwrap();
+        std::env::set_var("NETRC", &path);
+
+        let credentials = Credentials::resolve(&MirrorAuth::Netrc, "https://example.com:8443/dist");
+
+        assert_eq!(
+            basic(credentials.ok()),
+            Some((String::from("alice"), String::from("secret")))
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn it_fails_when_the_secret_isnt_set() {
+        let bearer = MirrorAuth::Bearer {
+            token_env: String::from("NENV_TEST_MISSING_TOKEN"),
+        };
+        let basic = MirrorAuth::Basic {
+            username: String::from("alice"),
+            password_env: String::from("NENV_TEST_MISSING_PASSWORD"),
+        };
+
+        assert_eq!(
+            Credentials::resolve(&bearer, "https://example.com").unwrap_err(),
+            "the environment variable NENV_TEST_MISSING_TOKEN is not set"
+        );
+        assert_eq!(
+            Credentials::resolve(&basic, "https://example.com").unwrap_err(),
+            "the environment variable NENV_TEST_MISSING_PASSWORD is not set"
+        );
+    }
+}
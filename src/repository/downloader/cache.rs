@@ -0,0 +1,231 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use miette::{Context, IntoDiagnostic, Result};
+use tokio::fs;
+
+use crate::{
+    config::{ArchiveFormat, Flavor},
+    consts::CACHE_DIR,
+    lock::FileLock,
+    versioning::SimpleVersion,
+};
+
+use super::{archive_file_name, download_lock_name, release_file_name};
+
+/// The files fetched from the dist directory of a version next to the archive
+pub(super) const RELEASE_FILES: [&str; 3] =
+    ["SHASUMS256.txt", "SHASUMS256.txt.sig", "SHASUMS256.txt.asc"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheEntryKind {
+    /// A downloaded node archive
+    Archive,
+    /// An archive whose download hasn't been completed
+    Partial,
+    /// The published checksums of a version and their signature
+    ReleaseFile,
+    /// A cached list of available versions
+    VersionIndex,
+    /// The cached release schedule
+    ReleaseSchedule,
+}
+
+/// A file in the download cache
+#[derive(Clone, Debug)]
+pub struct CacheEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub size: u64,
+    /// The time the file was downloaded or last used for an installation
+    pub last_used: SystemTime,
+    pub kind: CacheEntryKind,
+}
+
+/// Which files are removed when pruning the cache
+#[derive(Clone, Debug, Default)]
+pub struct PruneOptions {
+    /// Only removes files that haven't been used for this long
+    pub older_than: Option<Duration>,
+    /// The names of files that are never removed
+    pub keep: HashSet<String>,
+}
+
+impl CacheEntryKind {
+    fn from_file_name(name: &str) -> Self {
+        if name.starts_with("versions") && name.ends_with(".cache") {
+            CacheEntryKind::VersionIndex
+        } else if name == "schedule.cache" {
+            CacheEntryKind::ReleaseSchedule
+        } else if name.ends_with(".part") {
+            CacheEntryKind::Partial
+        } else if ArchiveFormat::from_file_name(name).is_some() {
+            CacheEntryKind::Archive
+        } else {
+            CacheEntryKind::ReleaseFile
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CacheEntryKind::Archive => "archive",
+            CacheEntryKind::Partial => "partial download",
+            CacheEntryKind::ReleaseFile => "checksums",
+            CacheEntryKind::VersionIndex => "version index",
+            CacheEntryKind::ReleaseSchedule => "release schedule",
+        }
+    }
+}
+
+impl CacheEntry {
+    /// Returns the time since the file was last used
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.last_used)
+            .unwrap_or_default()
+    }
+}
+
+/// Returns all files in the download cache ordered by name
+#[tracing::instrument(level = "debug")]
+pub async fn entries() -> Result<Vec<CacheEntry>> {
+    if !CACHE_DIR.exists() {
+        return Ok(Vec::new());
+    }
+    let mut dir = fs::read_dir(&*CACHE_DIR)
+        .await
+        .into_diagnostic()
+        .context("Reading cache directory")?;
+    let mut entries = Vec::new();
+
+    while let Some(entry) = dir.next_entry().await.into_diagnostic()? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let metadata = entry.metadata().await.into_diagnostic()?;
+
+        // temporary files of atomic writes are hidden
+        if !metadata.is_file() || name.starts_with('.') {
+            continue;
+        }
+        entries.push(CacheEntry {
+            path: entry.path(),
+            kind: CacheEntryKind::from_file_name(&name),
+            size: metadata.len(),
+            last_used: metadata.modified().into_diagnostic()?,
+            name,
+        });
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(entries)
+}
+
+/// Removes archives, partial downloads and checksums from the cache.
+/// The version index and the release schedule are never removed.
+#[tracing::instrument(level = "debug")]
+pub async fn prune(options: &PruneOptions) -> Result<Vec<CacheEntry>> {
+    let mut removed = Vec::new();
+
+    for entry in entries().await? {
+        if matches!(
+            entry.kind,
+            CacheEntryKind::VersionIndex | CacheEntryKind::ReleaseSchedule
+        ) || options.keep.contains(&entry.name)
+            || options.older_than.is_some_and(|age| entry.age() < age)
+        {
+            continue;
+        }
+        remove(&entry).await?;
+        removed.push(entry);
+    }
+
+    Ok(removed)
+}
+
+/// Removes all files from the cache.
+/// The version index and the release schedule are only removed when requested.
+#[tracing::instrument(level = "debug")]
+pub async fn clear(version_index: bool) -> Result<Vec<CacheEntry>> {
+    let mut removed = prune(&PruneOptions::default()).await?;
+
+    if version_index {
+        for entry in entries().await? {
+            remove(&entry).await?;
+            removed.push(entry);
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Removes the least recently used archives until
+/// their total size doesn't exceed the given size.
+/// Archives that are downloaded or used by any process are skipped.
+#[tracing::instrument(level = "debug")]
+pub async fn enforce_max_size(max_size: u64) -> Result<Vec<CacheEntry>> {
+    let mut archives = entries()
+        .await?
+        .into_iter()
+        .filter(|e| e.kind == CacheEntryKind::Archive)
+        .collect::<Vec<_>>();
+    archives.sort_by_key(|e| e.last_used);
+    let mut total = archives.iter().map(|e| e.size).sum::<u64>();
+    let mut removed = Vec::new();
+
+    for entry in archives {
+        if total <= max_size {
+            break;
+        }
+        let Some(_lock) = FileLock::try_exclusive(&download_lock_name(&entry.name))? else {
+            tracing::debug!("Not evicting {} because it is in use", entry.name);
+            continue;
+        };
+        remove(&entry).await?;
+        total -= entry.size;
+        removed.push(entry);
+    }
+
+    Ok(removed)
+}
+
+/// Marks a cached file as used so that it is evicted last
+pub async fn touch(path: &Path) -> Result<()> {
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .await
+        .into_diagnostic()?
+        .into_std()
+        .await;
+
+    tokio::task::spawn_blocking(move || file.set_modified(SystemTime::now()))
+        .await
+        .into_diagnostic()?
+        .into_diagnostic()
+        .with_context(|| format!("Updating modification time of {path:?}"))
+}
+
+/// Returns the names of all cached files that belong to the given build
+pub fn file_names(version: &SimpleVersion, flavor: Flavor) -> Vec<String> {
+    let archives = [
+        ArchiveFormat::TarXz,
+        ArchiveFormat::TarGz,
+        ArchiveFormat::Zip,
+    ]
+    .into_iter()
+    .map(|format| archive_file_name(version, format, flavor));
+    let release_files = RELEASE_FILES
+        .into_iter()
+        .map(|name| release_file_name(version, flavor, name));
+
+    archives.chain(release_files).collect()
+}
+
+async fn remove(entry: &CacheEntry) -> Result<()> {
+    fs::remove_file(&entry.path)
+        .await
+        .into_diagnostic()
+        .with_context(|| format!("Removing {} from the cache", entry.name))
+}
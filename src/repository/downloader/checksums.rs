@@ -0,0 +1,134 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::Path,
+    pin::Pin,
+    task::{Context as TaskContext, Poll},
+};
+
+use miette::{Context, IntoDiagnostic, Result};
+use sha2::{Digest, Sha256};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncWrite},
+};
+
+/// The list of sha256 checksums published for a node release
+/// in its `SHASUMS256.txt` file
+#[derive(Clone, Debug, Default)]
+pub struct Checksums {
+    checksums: HashMap<String, String>,
+}
+
+impl Checksums {
+    /// Parses the contents of a `SHASUMS256.txt` file.
+    /// Each line contains a hex encoded checksum followed by the file name
+    pub fn parse(contents: &str) -> Self {
+        let checksums = contents
+            .lines()
+            .filter_map(|l| {
+                let (checksum, file_name) = l.trim().split_once(char::is_whitespace)?;
+                Some((
+                    file_name.trim().trim_start_matches('*').to_owned(),
+                    checksum.to_lowercase(),
+                ))
+            })
+            .collect();
+
+        Self { checksums }
+    }
+
+    /// Returns the checksum for the given file name
+    pub fn get<S: AsRef<str>>(&self, file_name: S) -> Option<&String> {
+        self.checksums.get(file_name.as_ref())
+    }
+}
+
+/// Incrementally computes the sha256 of downloaded content
+#[derive(Default)]
+pub struct ChecksumHasher(Sha256);
+
+impl ChecksumHasher {
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    /// Feeds the contents of the file at the given path into the hasher
+    pub async fn update_from_file(&mut self, path: &Path) -> Result<()> {
+        let mut file = File::open(path)
+            .await
+            .into_diagnostic()
+            .context("Opening file to compute checksum")?;
+        let mut buf = vec![0u8; 64 * 1024];
+
+        loop {
+            let read = file
+                .read(&mut buf)
+                .await
+                .into_diagnostic()
+                .context("Reading file to compute checksum")?;
+            if read == 0 {
+                break;
+            }
+            self.update(&buf[..read]);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the hex encoded checksum
+    pub fn finish(self) -> String {
+        format!("{:x}", self.0.finalize())
+    }
+}
+
+/// A writer that computes the checksum of everything written to it
+pub struct HashingWriter<W> {
+    inner: W,
+    hasher: ChecksumHasher,
+}
+
+impl<W: AsyncWrite + Unpin> HashingWriter<W> {
+    pub fn new(inner: W, hasher: ChecksumHasher) -> Self {
+        Self { inner, hasher }
+    }
+
+    /// Returns the hex encoded checksum of the written content
+    pub fn finish(self) -> String {
+        self.hasher.finish()
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for HashingWriter<W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+
+        if let Poll::Ready(Ok(written)) = poll {
+            this.hasher.update(&buf[..written]);
+        }
+
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Computes the hex encoded sha256 of the file at the given path
+#[tracing::instrument(level = "debug")]
+pub async fn file_checksum(path: &Path) -> Result<String> {
+    let mut hasher = ChecksumHasher::default();
+    hasher.update_from_file(path).await?;
+
+    Ok(hasher.finish())
+}
@@ -1,9 +1,12 @@
 use std::{fs::File, io, path::Path};
 
+use indicatif::MultiProgress;
 use miette::Diagnostic;
 use miette::Result;
 use thiserror::Error;
 
+use crate::{config::ArchiveFormat, utils::progress_spinner};
+
 type ExtractResult<T> = Result<T, ExtractError>;
 
 /// An error that can occur during extraction
@@ -18,42 +21,87 @@ pub enum ExtractError {
     Zip(#[from] zip::result::ZipError),
 }
 
-pub fn extract_file(src: &Path, dst: &Path) -> ExtractResult<()> {
-    #[cfg(target_os = "windows")]
-    extract_zip(src, dst)?;
-    #[cfg(not(target_os = "windows"))]
-    extract_tar_gz(src, dst)?;
+/// Extracts the archive with the given format
+pub fn extract_file(
+    src: &Path,
+    dst: &Path,
+    format: ArchiveFormat,
+    progress: &MultiProgress,
+) -> ExtractResult<()> {
+    match format {
+        ArchiveFormat::TarXz => extract_tar_xz(src, dst, progress),
+        ArchiveFormat::TarGz => extract_tar_gz(src, dst, progress),
+        ArchiveFormat::Zip => extract_zip(src, dst, progress),
+    }
+}
+
+/// Extracts a tar archive with the given format while it is read from the reader.
+/// The reader is read to the end so that the checksum of the whole archive can be computed.
+pub fn extract_stream<R: io::Read>(
+    mut reader: R,
+    name: &Path,
+    dst: &Path,
+    format: ArchiveFormat,
+    progress: &MultiProgress,
+) -> ExtractResult<()> {
+    use libflate::gzip::Decoder;
+    use xz2::read::XzDecoder;
+
+    match format {
+        ArchiveFormat::TarXz => extract_tar(XzDecoder::new(&mut reader), name, dst, progress)?,
+        ArchiveFormat::TarGz => extract_tar(Decoder::new(&mut reader)?, name, dst, progress)?,
+        ArchiveFormat::Zip => {
+            return Err(ExtractError::Io(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "zip archives can't be extracted while they are downloaded",
+            )))
+        }
+    }
+    io::copy(&mut reader, &mut io::sink())?;
 
     Ok(())
 }
 
-#[cfg(not(target_os = "windows"))]
-fn extract_tar_gz(src: &Path, dst: &Path) -> ExtractResult<()> {
-    use crate::utils::progress_spinner;
+fn extract_tar_gz(src: &Path, dst: &Path, progress: &MultiProgress) -> ExtractResult<()> {
     use libflate::gzip::Decoder;
-    use tar::Archive;
 
     let reader = io::BufReader::new(File::open(src)?);
-    let decoder = Decoder::new(reader)?;
+    extract_tar(Decoder::new(reader)?, src, dst, progress)
+}
+
+fn extract_tar_xz(src: &Path, dst: &Path, progress: &MultiProgress) -> ExtractResult<()> {
+    use xz2::read::XzDecoder;
+
+    let reader = io::BufReader::new(File::open(src)?);
+    extract_tar(XzDecoder::new(reader), src, dst, progress)
+}
+
+fn extract_tar<R: io::Read>(
+    decoder: R,
+    src: &Path,
+    dst: &Path,
+    progress: &MultiProgress,
+) -> ExtractResult<()> {
+    use tar::Archive;
+
     let mut archive = Archive::new(decoder);
-    let pb = progress_spinner();
-    pb.set_message("Extracting tar.gz archive");
+    let pb = progress.add(progress_spinner());
+    pb.set_message(format!("Extracting {}", file_name(src)));
 
     archive.unpack(dst)?;
-    pb.finish_with_message("Archive extracted.");
+    pb.finish_with_message(format!("Extracted {}.", file_name(src)));
 
     Ok(())
 }
 
-#[cfg(target_os = "windows")]
-fn extract_zip(src: &Path, dst: &Path) -> ExtractResult<()> {
+fn extract_zip(src: &Path, dst: &Path, progress: &MultiProgress) -> ExtractResult<()> {
     use crate::utils::progress_bar;
     use std::fs;
     use zip::ZipArchive;
     let mut archive = ZipArchive::new(File::open(src)?)?;
 
-    let pb = progress_bar(archive.len() as u64);
-    pb.set_message("Extracting zip archive");
+    let pb = progress.add(progress_bar(archive.len() as u64));
+    pb.set_message(format!("Extracting {}", file_name(src)));
 
     for i in 0..archive.len() {
         let mut file = archive.by_index(i)?;
@@ -81,7 +129,13 @@ fn extract_zip(src: &Path, dst: &Path) -> ExtractResult<()> {
         }
         pb.inc(1);
     }
-    pb.finish_with_message("Archive extracted.");
+    pb.finish_with_message(format!("Extracted {}.", file_name(src)));
 
     Ok(())
 }
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use miette::{Context, IntoDiagnostic, Result};
+use reqwest::{Certificate, Client, NoProxy, Proxy};
+use tokio::fs;
+
+use crate::{config::HttpConfig, error::ReqwestError};
+
+/// The http client shared by all requests to the mirrors
+#[derive(Clone, Debug)]
+pub struct HttpClient {
+    client: Client,
+    read_timeout: Duration,
+    retries: u32,
+}
+
+impl HttpClient {
+    /// Creates a new client with the given configuration
+    #[tracing::instrument(level = "debug")]
+    pub async fn new(config: &HttpConfig) -> Result<Self> {
+        let user_agent = config
+            .user_agent
+            .to_owned()
+            .unwrap_or_else(|| format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")));
+        let mut builder = Client::builder()
+            .user_agent(user_agent)
+            .connect_timeout(Duration::from_secs(config.connect_timeout));
+
+        if let Some(proxy) = &config.proxy {
+            let no_proxy = config
+                .no_proxy
+                .as_ref()
+                .and_then(|n| NoProxy::from_string(n))
+                .or_else(NoProxy::from_env);
+            let proxy = Proxy::all(proxy)
+                .map_err(ReqwestError::from)
+                .context("Invalid proxy url")?
+                .no_proxy(no_proxy);
+            builder = builder.proxy(proxy);
+        }
+
+        for path in &config.ca_certificates {
+            let pem = fs::read(path)
+                .await
+                .into_diagnostic()
+                .with_context(|| format!("Reading CA certificates from {path:?}"))?;
+            let certificates = Certificate::from_pem_bundle(&pem)
+                .map_err(ReqwestError::from)
+                .with_context(|| format!("Parsing CA certificates from {path:?}"))?;
+
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        let client = builder
+            .build()
+            .map_err(ReqwestError::from)
+            .context("Creating http client")?;
+
+        Ok(Self {
+            client,
+            read_timeout: Duration::from_secs(config.read_timeout),
+            retries: config.retries,
+        })
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// The maximum time to wait for data from the server
+    pub fn read_timeout(&self) -> Duration {
+        self.read_timeout
+    }
+
+    /// The number of times a request to an unavailable mirror is retried
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// The time to wait before the given retry attempt
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        Duration::from_millis(500 * 2u64.pow(attempt.min(6)))
+    }
+}
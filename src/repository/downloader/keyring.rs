@@ -0,0 +1,468 @@
+use std::{io::Cursor, path::Path};
+
+use miette::{miette, Context, IntoDiagnostic, Result};
+use pgp::{
+    cleartext::CleartextSignedMessage, types::PublicKeyTrait, ArmorOptions, Deserializable,
+    SignedPublicKey, StandaloneSignature,
+};
+use tokio::fs;
+
+use crate::consts::KEYRING_DIR;
+
+/// The release signing keys shipped with nenv
+const BUNDLED_KEYS: &str = include_str!("../../../keys/nodejs-release-keys.asc");
+
+/// The keys used to verify the signatures of release checksums.
+/// It consists of the keys bundled with nenv and the keys
+/// imported by the user.
+pub struct Keyring {
+    keys: Vec<ReleaseKey>,
+}
+
+pub struct ReleaseKey {
+    key: SignedPublicKey,
+    source: KeySource,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeySource {
+    Bundled,
+    Imported,
+}
+
+/// A signature of a `SHASUMS256.txt` file
+pub enum ChecksumSignature {
+    /// A detached signature as published in `SHASUMS256.txt.sig`
+    Detached(StandaloneSignature),
+    /// A clearsigned message as published in `SHASUMS256.txt.asc`
+    Cleartext(CleartextSignedMessage),
+}
+
+impl Keyring {
+    /// Loads the bundled keys and all keys from the keyring directory
+    #[tracing::instrument(level = "debug")]
+    pub async fn load() -> Result<Self> {
+        Self::load_from(BUNDLED_KEYS.as_bytes(), &KEYRING_DIR).await
+    }
+
+    async fn load_from(bundled: &[u8], keyring_dir: &Path) -> Result<Self> {
+        let mut keys = parse_keys(bundled)
+            .context("Parsing bundled release keys")?
+            .into_iter()
+            .map(|key| ReleaseKey {
+                key,
+                source: KeySource::Bundled,
+            })
+            .collect::<Vec<_>>();
+
+        if keyring_dir.exists() {
+            let mut iter = fs::read_dir(keyring_dir).await.into_diagnostic()?;
+
+            while let Some(entry) = iter.next_entry().await.into_diagnostic()? {
+                let contents = fs::read(entry.path()).await.into_diagnostic()?;
+
+                match parse_keys(&contents) {
+                    Ok(imported) => keys.extend(imported.into_iter().map(|key| ReleaseKey {
+                        key,
+                        source: KeySource::Imported,
+                    })),
+                    Err(e) => tracing::error!("Failed to parse key {:?}: {e}", entry.path()),
+                }
+            }
+        }
+        if keys.is_empty() {
+            return Err(miette!(
+                "The keyring doesn't contain any release keys. Import the keys from nodejs/release-keys with `nenv keys import <file>`."
+            ));
+        }
+
+        Ok(Self { keys })
+    }
+
+    /// Imports all keys from the given file and returns the imported keys
+    #[tracing::instrument(level = "debug")]
+    pub async fn import(path: &Path) -> Result<Vec<ReleaseKey>> {
+        let contents = fs::read(path)
+            .await
+            .into_diagnostic()
+            .context("Reading key file")?;
+        let keys = parse_keys(&contents)?;
+
+        if keys.is_empty() {
+            return Err(miette!(
+                "The file {path:?} doesn't contain any public keys."
+            ));
+        }
+        if !KEYRING_DIR.exists() {
+            fs::create_dir_all(&*KEYRING_DIR)
+                .await
+                .into_diagnostic()
+                .context("Creating keyring directory")?;
+        }
+
+        let mut imported = Vec::new();
+
+        for key in keys {
+            let key = ReleaseKey {
+                key,
+                source: KeySource::Imported,
+            };
+            let armored = key
+                .key
+                .to_armored_string(ArmorOptions::default())
+                .into_diagnostic()?;
+            fs::write(
+                KEYRING_DIR.join(format!("{}.asc", key.fingerprint())),
+                armored,
+            )
+            .await
+            .into_diagnostic()
+            .context("Writing key to keyring")?;
+            imported.push(key);
+        }
+
+        Ok(imported)
+    }
+
+    /// Removes an imported key by its fingerprint or long key id
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn remove(&mut self, query: &str) -> Result<ReleaseKey> {
+        let query = query.replace(' ', "").to_uppercase();
+        let index = self
+            .keys
+            .iter()
+            .position(|k| k.matches(&query))
+            .ok_or_else(|| miette!("No key with the fingerprint {query} found."))?;
+
+        if self.keys[index].source == KeySource::Bundled {
+            return Err(miette!(
+                "The key {query} is bundled with nenv and can't be removed."
+            ));
+        }
+        let key = self.keys.remove(index);
+        let path = KEYRING_DIR.join(format!("{}.asc", key.fingerprint()));
+
+        if path.exists() {
+            fs::remove_file(path)
+                .await
+                .into_diagnostic()
+                .context("Removing key from keyring")?;
+        }
+
+        Ok(key)
+    }
+
+    pub fn keys(&self) -> &[ReleaseKey] {
+        &self.keys
+    }
+
+    /// Verifies the signature of the given data against all known keys
+    /// and returns the key that created the signature
+    pub fn verify(&self, signature: &ChecksumSignature, data: &[u8]) -> Option<&ReleaseKey> {
+        self.keys.iter().find(|k| k.verifies(signature, data))
+    }
+}
+
+impl ReleaseKey {
+    /// Returns the hex encoded fingerprint of the primary key
+    pub fn fingerprint(&self) -> String {
+        self.key
+            .fingerprint()
+            .as_bytes()
+            .iter()
+            .map(|b| format!("{b:02X}"))
+            .collect()
+    }
+
+    pub fn user_ids(&self) -> Vec<String> {
+        self.key
+            .details
+            .users
+            .iter()
+            .map(|u| u.id.id().to_string())
+            .collect()
+    }
+
+    pub fn source(&self) -> KeySource {
+        self.source
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        query.len() >= 16 && self.fingerprint().ends_with(query)
+    }
+
+    fn verifies(&self, signature: &ChecksumSignature, data: &[u8]) -> bool {
+        verify_with(&self.key, signature, data)
+            || self
+                .key
+                .public_subkeys
+                .iter()
+                .any(|k| verify_with(k, signature, data))
+    }
+}
+
+impl ChecksumSignature {
+    /// Parses a signature file. Binary and armored detached signatures
+    /// as well as clearsigned messages are supported.
+    pub fn parse(contents: &[u8]) -> Result<Self> {
+        let text = String::from_utf8_lossy(contents);
+
+        let signature = if text.contains("-----BEGIN PGP SIGNED MESSAGE-----") {
+            let (msg, _) = CleartextSignedMessage::from_string(&text).into_diagnostic()?;
+            Self::Cleartext(msg)
+        } else if text.contains("-----BEGIN PGP SIGNATURE-----") {
+            let (sig, _) =
+                StandaloneSignature::from_armor_single(Cursor::new(contents)).into_diagnostic()?;
+            Self::Detached(sig)
+        } else {
+            Self::Detached(
+                StandaloneSignature::from_bytes(Cursor::new(contents)).into_diagnostic()?,
+            )
+        };
+
+        Ok(signature)
+    }
+}
+
+/// Parses armored or binary public keys
+fn parse_keys(contents: &[u8]) -> Result<Vec<SignedPublicKey>> {
+    let text = String::from_utf8_lossy(contents);
+
+    if text.contains("-----BEGIN PGP PUBLIC KEY BLOCK-----") {
+        let (keys, _) = SignedPublicKey::from_armor_many(Cursor::new(contents))
+            .into_diagnostic()
+            .context("Parsing armored public keys")?;
+        keys.collect::<Result<Vec<_>, _>>()
+            .into_diagnostic()
+            .context("Parsing armored public keys")
+    } else if text.trim().is_empty() {
+        Ok(Vec::new())
+    } else {
+        SignedPublicKey::from_bytes_many(Cursor::new(contents))
+            .collect::<Result<Vec<_>, _>>()
+            .into_diagnostic()
+            .context("Parsing binary public keys")
+    }
+}
+
+fn verify_with(key: &impl PublicKeyTrait, signature: &ChecksumSignature, data: &[u8]) -> bool {
+    match signature {
+        ChecksumSignature::Detached(sig) => sig.verify(key, data).is_ok(),
+        ChecksumSignature::Cleartext(msg) => {
+            msg.verify(key).is_ok()
+                && normalize_text(&msg.signed_text())
+                    == normalize_text(&String::from_utf8_lossy(data))
+        }
+    }
+}
+
+/// Normalizes line endings and trailing whitespace which aren't part of the signed text
+fn normalize_text(text: &str) -> String {
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pgp::{
+        crypto::hash::HashAlgorithm,
+        packet::{SignatureConfig, SignatureType, Subpacket, SubpacketData},
+        ser::Serialize,
+        KeyType, SecretKeyParamsBuilder, SignedSecretKey,
+    };
+
+    use super::*;
+    use crate::{
+        config::HttpConfig,
+        repository::downloader::{http::HttpClient, mirror::Mirror},
+    };
+
+    const VERSION: &str = "v20.11.0";
+    const CHECKSUMS: &str = "\
+        0b8b3b4a5b6c  node-v20.11.0-linux-x64.tar.xz\n\
+        \n\
+        1c9c4c5b6c7d  node-v20.11.0-linux-arm64.tar.xz\n";
+
+    fn generate_key() -> SignedSecretKey {
+        let mut rng = rand::thread_rng();
+        let params = SecretKeyParamsBuilder::default()
+            .key_type(KeyType::EdDSALegacy)
+            .can_certify(true)
+            .can_sign(true)
+            .primary_user_id("Test Releaser <releaser@example.com>".into())
+            .build()
+            .unwrap();
+        params
+            .generate(&mut rng)
+            .unwrap()
+            .sign(&mut rng, String::new)
+            .unwrap()
+    }
+
+    fn detached_signature(key: &SignedSecretKey, data: &[u8]) -> Vec<u8> {
+        let mut config = SignatureConfig::v4(
+            SignatureType::Binary,
+            key.algorithm(),
+            HashAlgorithm::SHA2_256,
+        );
+        config.hashed_subpackets = vec![Subpacket::regular(SubpacketData::IssuerFingerprint(
+            key.fingerprint(),
+        ))];
+        config.unhashed_subpackets = vec![Subpacket::regular(SubpacketData::Issuer(key.key_id()))];
+        let signature = config.sign(key, String::new, data).unwrap();
+
+        StandaloneSignature::new(signature).to_bytes().unwrap()
+    }
+
+    fn clearsigned(key: &SignedSecretKey, text: &str) -> Vec<u8> {
+        CleartextSignedMessage::sign(rand::thread_rng(), text, key, String::new)
+            .unwrap()
+            .to_armored_bytes(ArmorOptions::default())
+            .unwrap()
+    }
+
+    fn keyring_with(key: &SignedSecretKey) -> Keyring {
+        Keyring {
+            keys: vec![ReleaseKey {
+                key: key.clone().into(),
+                source: KeySource::Imported,
+            }],
+        }
+    }
+
+    /// Creates a mirror directory with the checksums and their signatures
+    fn create_mirror(name: &str, key: &SignedSecretKey) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nenv-test-{name}-{}", std::process::id()));
+        let release_dir = dir.join(VERSION);
+        std::fs::create_dir_all(&release_dir).unwrap();
+        std::fs::write(release_dir.join("SHASUMS256.txt"), CHECKSUMS).unwrap();
+        std::fs::write(
+            release_dir.join("SHASUMS256.txt.sig"),
+            detached_signature(key, CHECKSUMS.as_bytes()),
+        )
+        .unwrap();
+        std::fs::write(
+            release_dir.join("SHASUMS256.txt.asc"),
+            clearsigned(key, CHECKSUMS),
+        )
+        .unwrap();
+
+        dir
+    }
+
+    async fn fetch(dir: &Path, name: &str) -> Vec<u8> {
+        let client = HttpClient::new(&HttpConfig::default()).await.unwrap();
+        Mirror::new(format!("file://{}", dir.display()), None)
+            .get(&client, &format!("{VERSION}/{name}"), 0)
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap()
+    }
+
+    async fn verify_from_mirror(name: &str, signature_file: &str) {
+        let key = generate_key();
+        let dir = create_mirror(name, &key);
+        let keyring = keyring_with(&key);
+
+        let checksums = fetch(&dir, "SHASUMS256.txt").await;
+        let signature = ChecksumSignature::parse(&fetch(&dir, signature_file).await).unwrap();
+        let tampered = String::from_utf8(checksums.clone())
+            .unwrap()
+            .replace("0b8b3b4a5b6c", "0b8b3b4a5b6d");
+        let without_blank_line = CHECKSUMS.replace("\n\n", "\n");
+
+        assert!(keyring.verify(&signature, &checksums).is_some());
+        assert!(keyring.verify(&signature, tampered.as_bytes()).is_none());
+        assert!(keyring
+            .verify(&signature, without_blank_line.as_bytes())
+            .is_none());
+        assert!(keyring_with(&generate_key())
+            .verify(&signature, &checksums)
+            .is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_verifies_detached_signatures() {
+        verify_from_mirror("detached", "SHASUMS256.txt.sig").await;
+    }
+
+    #[tokio::test]
+    async fn it_verifies_clearsigned_checksums() {
+        verify_from_mirror("clearsigned", "SHASUMS256.txt.asc").await;
+    }
+
+    #[test]
+    fn it_ignores_line_endings_and_trailing_whitespace() {
+        let key = generate_key();
+        let signature = ChecksumSignature::parse(&clearsigned(&key, CHECKSUMS)).unwrap();
+        let crlf = CHECKSUMS.replace('\n', " \r\n");
+
+        assert!(keyring_with(&key)
+            .verify(&signature, crlf.as_bytes())
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn it_loads_imported_keys_without_bundled_keys() {
+        let key = generate_key();
+        let dir = std::env::temp_dir().join(format!("nenv-test-keyring-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let public_key: SignedPublicKey = key.clone().into();
+        std::fs::write(
+            dir.join("imported.asc"),
+            public_key
+                .to_armored_string(ArmorOptions::default())
+                .unwrap(),
+        )
+        .unwrap();
+
+        let keyring = Keyring::load_from(b"", &dir).await.unwrap();
+        let signature =
+            ChecksumSignature::parse(&detached_signature(&key, CHECKSUMS.as_bytes())).unwrap();
+
+        assert_eq!(keyring.keys().len(), 1);
+        assert_eq!(keyring.keys()[0].source(), KeySource::Imported);
+        assert!(keyring.verify(&signature, CHECKSUMS.as_bytes()).is_some());
+        assert!(Keyring::load_from(b"", &dir.join("missing")).await.is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs access to nodejs.org"]
+    async fn it_verifies_published_checksums_with_the_bundled_keys() {
+        let client = HttpClient::new(&HttpConfig::default()).await.unwrap();
+        let mirror = Mirror::new("https://nodejs.org/dist", None);
+        let fetch = |name: &'static str| {
+            let client = &client;
+            let mirror = &mirror;
+            async move {
+                mirror
+                    .get(client, &format!("{VERSION}/{name}"), 0)
+                    .await
+                    .unwrap()
+                    .bytes()
+                    .await
+                    .unwrap()
+            }
+        };
+        let checksums = fetch("SHASUMS256.txt").await;
+        let signature = ChecksumSignature::parse(&fetch("SHASUMS256.txt.sig").await).unwrap();
+        let keyring = Keyring::load_from(BUNDLED_KEYS.as_bytes(), Path::new("/nonexistent"))
+            .await
+            .unwrap();
+
+        let key = keyring.verify(&signature, &checksums).unwrap();
+        assert_eq!(key.source(), KeySource::Bundled);
+    }
+}
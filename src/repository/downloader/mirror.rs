@@ -0,0 +1,273 @@
+use std::{fmt, io::SeekFrom, path::PathBuf, time::Duration};
+
+use bytes::Bytes;
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
+use miette::{IntoDiagnostic, Result};
+use reqwest::{header, StatusCode};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt},
+};
+
+use crate::{
+    config::MirrorAuth,
+    error::{FetchError, ReqwestError},
+    utils::redact_url,
+};
+
+use super::{auth::Credentials, http::HttpClient};
+
+/// A server or directory that provides the contents of the nodejs dist directory
+#[derive(Clone)]
+pub struct Mirror {
+    url: String,
+    auth: Option<MirrorAuth>,
+}
+
+/// The response for a file requested from a mirror
+pub struct MirrorResponse {
+    /// Whether only the requested range starting from the offset is returned
+    pub partial: bool,
+    /// Whether the file hasn't changed since it was cached.
+    /// The response has no content in this case.
+    pub not_modified: bool,
+    /// The length of the returned content if known
+    pub content_length: Option<u64>,
+    /// The validators to revalidate a cached copy of the file
+    pub validators: CacheValidators,
+    stream: BoxStream<'static, Result<Bytes>>,
+}
+
+/// The `ETag` and `Last-Modified` headers of a response
+/// used for conditional requests
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CacheValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Mirror {
+    pub fn new<S: AsRef<str>>(url: S, auth: Option<MirrorAuth>) -> Self {
+        Self {
+            url: url.as_ref().trim_end_matches('/').to_owned(),
+            auth,
+        }
+    }
+
+    /// Returns the mirror of a subdirectory that uses the same authentication
+    pub fn join(&self, dir: &str) -> Self {
+        Self::new(format!("{}/{dir}", self.url), self.auth.to_owned())
+    }
+
+    /// Returns if the mirror is a local directory
+    pub fn is_local(&self) -> bool {
+        self.url.starts_with("file://")
+    }
+
+    /// Returns the url of the mirror without any credentials
+    pub fn url(&self) -> String {
+        redact_url(&self.url)
+    }
+
+    /// Requests a file relative to the mirrors base url.
+    /// If an offset is given only the remaining bytes are requested.
+    pub async fn get(
+        &self,
+        client: &HttpClient,
+        path: &str,
+        offset: u64,
+    ) -> Result<MirrorResponse, FetchError> {
+        self.request(client, path, offset, None).await
+    }
+
+    /// Requests a file relative to the mirrors base url.
+    /// If validators of a cached copy are given, the file is only
+    /// returned when it has been modified.
+    #[tracing::instrument(level = "debug", skip(client))]
+    pub async fn request(
+        &self,
+        client: &HttpClient,
+        path: &str,
+        offset: u64,
+        validators: Option<&CacheValidators>,
+    ) -> Result<MirrorResponse, FetchError> {
+        if let Some(dir) = self.url.strip_prefix("file://") {
+            self.get_file(PathBuf::from(dir), path, offset).await
+        } else {
+            self.get_http(client, path, offset, validators).await
+        }
+    }
+
+    async fn get_http(
+        &self,
+        client: &HttpClient,
+        path: &str,
+        offset: u64,
+        validators: Option<&CacheValidators>,
+    ) -> Result<MirrorResponse, FetchError> {
+        let url = format!("{}/{path}", self.url());
+        let mut req = client.client().get(format!("{}/{path}", self.url));
+
+        if let Some(auth) = &self.auth {
+            let credentials =
+                Credentials::resolve(auth, &self.url).map_err(|reason| FetchError::Auth {
+                    url: url.to_owned(),
+                    reason,
+                })?;
+            req = credentials.apply(req);
+        }
+        if offset > 0 {
+            req = req.header(header::RANGE, format!("bytes={offset}-"));
+        }
+        if let Some(validators) = validators {
+            if let Some(etag) = &validators.etag {
+                req = req.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                req = req.header(header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let read_timeout = client.read_timeout();
+        let res = tokio::time::timeout(read_timeout, req.send())
+            .await
+            .map_err(|_| FetchError::Timeout {
+                url: url.to_owned(),
+            })?
+            .map_err(|e| FetchError::Request {
+                url: url.to_owned(),
+                caused_by: e.without_url(),
+            })?;
+
+        let not_modified = res.status() == StatusCode::NOT_MODIFIED;
+
+        if !res.status().is_success() && !not_modified {
+            return Err(FetchError::Status {
+                url,
+                status: res.status(),
+            });
+        }
+        let header_value = |name| {
+            res.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        };
+        let validators = CacheValidators {
+            etag: header_value(header::ETAG),
+            last_modified: header_value(header::LAST_MODIFIED),
+        };
+
+        Ok(MirrorResponse {
+            partial: res.status() == StatusCode::PARTIAL_CONTENT,
+            not_modified,
+            content_length: res.content_length(),
+            validators,
+            stream: with_read_timeout(
+                res.bytes_stream()
+                    .map_err(|e| ReqwestError::from(e.without_url()).into())
+                    .boxed(),
+                url,
+                read_timeout,
+            ),
+        })
+    }
+
+    async fn get_file(
+        &self,
+        dir: PathBuf,
+        path: &str,
+        offset: u64,
+    ) -> Result<MirrorResponse, FetchError> {
+        let url = format!("{}/{path}", self.url);
+        let io_error = |e| FetchError::Io {
+            url: url.to_owned(),
+            available: dir.exists(),
+            caused_by: e,
+        };
+        let mut file = File::open(dir.join(path)).await.map_err(io_error)?;
+        let len = file.metadata().await.map_err(io_error)?.len();
+
+        if offset > len {
+            return Err(FetchError::Status {
+                url,
+                status: StatusCode::RANGE_NOT_SATISFIABLE,
+            });
+        }
+        file.seek(SeekFrom::Start(offset)).await.map_err(io_error)?;
+
+        let stream = futures::stream::unfold(file, |mut file| async move {
+            let mut buf = vec![0u8; 64 * 1024];
+
+            match file.read(&mut buf).await {
+                Ok(0) => None,
+                Ok(read) => {
+                    buf.truncate(read);
+                    Some((Ok(Bytes::from(buf)), file))
+                }
+                Err(e) => Some((Err(e).into_diagnostic(), file)),
+            }
+        });
+
+        Ok(MirrorResponse {
+            partial: offset > 0,
+            not_modified: false,
+            content_length: Some(len - offset),
+            validators: CacheValidators::default(),
+            stream: stream.boxed(),
+        })
+    }
+}
+
+impl fmt::Display for Mirror {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.url().fmt(f)
+    }
+}
+
+impl fmt::Debug for Mirror {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mirror")
+            .field("url", &self.url())
+            .field("auth", &self.auth)
+            .finish()
+    }
+}
+
+impl MirrorResponse {
+    /// Returns the stream of the response body
+    pub fn bytes_stream(self) -> BoxStream<'static, Result<Bytes>> {
+        self.stream
+    }
+
+    /// Reads the complete response body
+    pub async fn bytes(self) -> Result<Vec<u8>> {
+        let mut contents = Vec::new();
+        let mut stream = self.stream;
+
+        while let Some(chunk) = stream.next().await {
+            contents.extend_from_slice(&chunk?);
+        }
+
+        Ok(contents)
+    }
+}
+
+/// Fails the stream when no data has been received for the given duration
+fn with_read_timeout(
+    stream: BoxStream<'static, Result<Bytes>>,
+    url: String,
+    timeout: Duration,
+) -> BoxStream<'static, Result<Bytes>> {
+    futures::stream::unfold(stream, move |mut stream| {
+        let url = url.to_owned();
+        async move {
+            match tokio::time::timeout(timeout, stream.next()).await {
+                Ok(Some(chunk)) => Some((chunk, stream)),
+                Ok(None) => None,
+                Err(_) => Some((Err(FetchError::Timeout { url }.into()), stream)),
+            }
+        }
+    })
+    .boxed()
+}
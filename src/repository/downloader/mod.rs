@@ -1,150 +1,1239 @@
 use std::{
-    cmp::min,
+    collections::HashMap,
     fmt::Debug,
     fmt::Display,
     path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
 use crate::{
-    config::ConfigAccess,
-    consts::{CACHE_DIR, NODE_ARCHIVE_SUFFIX, NODE_VERSIONS_DIR},
-    error::ReqwestError,
-    utils::progress_bar,
-    versioning::SimpleVersion,
+    config::{ArchiveFormat, ConfigAccess, EolPolicy, Flavor, MirrorConfig, SignaturePolicy},
+    consts::{ARCH, CACHE_DIR, OS},
+    error::{
+        ChecksumMismatchError, EndOfLifeError, FetchError, MissingChecksumError,
+        SignatureVerificationError, UnavailableArchiveError,
+    },
+    lock::FileLock,
+    utils::{format_age, progress_bar, progress_bytes_spinner},
+    versioning::{Channel, SimpleVersion, VersionMetadata},
 };
 
-use self::versions::Versions;
+use self::{
+    checksums::{file_checksum, ChecksumHasher, Checksums, HashingWriter},
+    http::HttpClient,
+    keyring::{ChecksumSignature, Keyring},
+    mirror::{CacheValidators, Mirror, MirrorResponse},
+    schedule::ReleaseSchedule,
+    versions::Versions,
+};
 
-use futures::StreamExt;
+use crossterm::style::Stylize;
+use futures::{future, StreamExt};
+use indicatif::MultiProgress;
 use miette::{miette, Context, IntoDiagnostic, Result};
+use reqwest::{StatusCode, Url};
 use tokio::{
-    fs::File,
+    fs::{self, File, OpenOptions},
     io::{AsyncWrite, AsyncWriteExt, BufWriter},
 };
+mod auth;
+pub mod cache;
+pub mod checksums;
 mod extract;
+mod http;
+pub mod keyring;
+pub mod mirror;
+pub mod schedule;
+mod stream;
 mod version_info;
 pub mod versions;
 pub use version_info::VersionInfo;
+use xkcd_unreachable::xkcd_unreachable;
 
 #[derive(Clone)]
 pub struct NodeDownloader {
     config: ConfigAccess,
-    versions: Option<Versions>,
+    client: HttpClient,
+    /// The lists of available versions of each channel that has been loaded
+    versions: HashMap<Channel, Versions>,
+    /// Only local mirrors are used when offline
+    offline: bool,
+    /// Renders the progress of concurrent downloads and extractions
+    progress: MultiProgress,
+}
+
+/// The archive that is downloaded for a version
+#[derive(Clone, Debug)]
+struct ReleaseArchive {
+    file_name: String,
+    format: ArchiveFormat,
+    /// The published sha256 checksum of the archive
+    checksum: String,
 }
 
 impl NodeDownloader {
-    pub fn new(config: ConfigAccess) -> Self {
-        Self {
+    pub async fn new(config: ConfigAccess, offline: bool) -> Result<Self> {
+        let client = HttpClient::new(&config.get().await.download.http).await?;
+
+        Ok(Self {
             config,
-            versions: None,
-        }
+            client,
+            versions: HashMap::new(),
+            offline,
+            progress: MultiProgress::new(),
+        })
     }
 
-    /// Returns the list of available node versions
+    /// Returns the list of released node versions
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn versions(&mut self) -> Result<&Versions> {
-        if self.versions.is_none() {
-            self.versions = Some(self.load_versions().await?);
+        self.channel_versions(Channel::Release).await
+    }
+
+    /// Returns the list of node versions published in the given channel
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn channel_versions(&mut self, channel: Channel) -> Result<&Versions> {
+        if !self.versions.contains_key(&channel) {
+            let versions = self.load_versions(channel).await?;
+            self.versions.insert(channel, versions);
         }
 
-        Ok(self.versions.as_ref().unwrap())
+        Ok(&self.versions[&channel])
     }
 
-    async fn load_versions(&self) -> Result<Versions> {
-        let versions = if let Some(v) = Versions::load().await {
-            v
-        } else {
-            let versions = reqwest::get(format!("{}/index.json", self.base_url().await))
-                .await
-                .map_err(ReqwestError::from)
-                .context("Fetching versions")?
-                .json()
-                .await
-                .map_err(ReqwestError::from)
-                .context("Parsing versions response")?;
-            let v = Versions::new(versions);
-            v.save().await?;
-            v
+    /// Refreshes the list of released node versions and the lists of
+    /// all loaded channels regardless of their age.
+    /// Returns if any of the lists has changed.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn refresh_versions(&mut self) -> Result<bool> {
+        let mut channels = self.versions.keys().copied().collect::<Vec<_>>();
+
+        if !channels.contains(&Channel::Release) {
+            channels.push(Channel::Release);
+        }
+        let mut changed = false;
+
+        for channel in channels {
+            let cached = match self.versions.remove(&channel) {
+                Some(v) => Some(v),
+                None => Versions::load(self.flavor().await, channel).await,
+            };
+            let (versions, channel_changed) = self.revalidate_versions(channel, cached).await?;
+            self.versions.insert(channel, versions);
+            changed |= channel_changed;
+        }
+
+        Ok(changed)
+    }
+
+    /// Loads the cached list of versions and revalidates it if it is expired.
+    /// A stale list is used when it can't be revalidated.
+    async fn load_versions(&self, channel: Channel) -> Result<Versions> {
+        let ttl = Duration::from_secs(self.config.get().await.download.versions_ttl);
+
+        match Versions::load(self.flavor().await, channel).await {
+            Some(v) if self.offline || !v.is_expired(ttl) => Ok(v),
+            Some(v) => {
+                let age = v.age();
+
+                match self.revalidate_versions(channel, Some(v.clone())).await {
+                    Ok((v, _)) => Ok(v),
+                    Err(e) => {
+                        eprintln!(
+                            "{} Failed to refresh the list of node versions ({}). Using the cached list from {} ago.",
+                            "Warning:".yellow().bold(),
+                            e.root_cause(),
+                            format_age(age)
+                        );
+                        Ok(v)
+                    }
+                }
+            }
+            None => Ok(self.revalidate_versions(channel, None).await?.0),
+        }
+    }
+
+    /// Fetches the list of versions if it has been modified since the given cached one.
+    /// Returns the up to date list and if it has changed.
+    async fn revalidate_versions(
+        &self,
+        channel: Channel,
+        cached: Option<Versions>,
+    ) -> Result<(Versions, bool)> {
+        let res = self
+            .request(
+                channel,
+                "index.json",
+                0,
+                cached.as_ref().map(Versions::validators),
+            )
+            .await
+            .context("Fetching versions")?;
+
+        if let (true, Some(mut cached)) = (res.not_modified, cached) {
+            tracing::debug!("The cached versions are up to date");
+            cached.touch();
+            cached.save().await?;
+
+            return Ok((cached, false));
+        }
+        let validators = res.validators.to_owned();
+        let contents = res.bytes().await.context("Reading versions response")?;
+        let versions = serde_json::from_slice(&contents)
+            .into_diagnostic()
+            .context("Parsing versions response")?;
+        let v = Versions::new(versions, self.flavor().await, channel, validators);
+        v.save().await?;
+
+        Ok((v, true))
+    }
+
+    /// Returns the release schedule from the cache and revalidates it if it is expired.
+    /// The bundled schedule is used when the configured one can't be fetched.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn schedule(&self) -> ReleaseSchedule {
+        let ttl = Duration::from_secs(self.config.get().await.download.versions_ttl);
+
+        match ReleaseSchedule::load().await {
+            Some(s) if self.offline || !s.is_expired(ttl) => s,
+            cached => match self.revalidate_schedule(cached.as_ref()).await {
+                Ok(s) => s,
+                Err(e) => {
+                    tracing::debug!("Failed to fetch the release schedule: {e}");
+                    // the fallback is cached so that the mirror isn't asked again before it expires.
+                    // Only the time of the request is kept for the bundled copy.
+                    let mut schedule = cached
+                        .filter(|s| !s.is_bundled())
+                        .unwrap_or_else(ReleaseSchedule::bundled);
+                    schedule.touch();
+
+                    if let Err(e) = schedule.save().await {
+                        tracing::debug!("Failed to cache the release schedule: {e}");
+                    }
+                    schedule
+                }
+            },
+        }
+    }
+
+    /// Fetches the release schedule if it has been modified since the given cached one
+    async fn revalidate_schedule(
+        &self,
+        cached: Option<&ReleaseSchedule>,
+    ) -> Result<ReleaseSchedule> {
+        let url = self.config.get().await.download.schedule_url.to_owned();
+        let (_, res) = self
+            .request_url(&url, cached.map(ReleaseSchedule::validators))
+            .await
+            .context("Fetching release schedule")?;
+
+        let schedule = match cached {
+            Some(cached) if res.not_modified => {
+                let mut cached = cached.to_owned();
+                cached.touch();
+                cached
+            }
+            _ => {
+                let validators = res.validators.to_owned();
+                let contents = res.bytes().await.context("Reading release schedule")?;
+                ReleaseSchedule::parse(&contents, validators)?
+            }
+        };
+        schedule.save().await?;
+
+        Ok(schedule)
+    }
+
+    /// Checks if the given version has reached its end of life and handles it
+    /// according to the configured eol policy
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn check_end_of_life(&self, version: &SimpleVersion) -> Result<()> {
+        let schedule = self.schedule().await;
+
+        let Some(end) = schedule.end_of_life(version) else {
+            return Ok(());
+        };
+        let error = EndOfLifeError {
+            major: version.major,
+            end: end.to_owned(),
         };
 
-        Ok(versions)
+        match self.config.get().await.node.eol_policy {
+            EolPolicy::Warn => {
+                self.progress
+                    .suspend(|| eprintln!("{} {error}", "Warning:".yellow().bold()));
+                Ok(())
+            }
+            EolPolicy::Refuse => Err(error.into()),
+        }
     }
 
-    /// Downloads a specified node version to the repository
+    /// Downloads a specified node version and extracts it into the given directory
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn download(&self, version: &SimpleVersion) -> Result<()> {
-        let archive_path = self.download_archive_to_cache(version).await?;
-        self.extract_archive(version, &archive_path)?;
+    pub async fn download(&self, info: &VersionMetadata, dst: &Path) -> Result<()> {
+        self.check_end_of_life(&info.version).await?;
+        let _lock = lock_cache().await?;
+        let archive = self.release_archive(info).await?;
+        let keep_archives = self.config.get().await.download.keep_archives;
+
+        if !keep_archives
+            && archive.format != ArchiveFormat::Zip
+            && !CACHE_DIR.join(&archive.file_name).exists()
+        {
+            return self.stream_archive(&info.version, &archive, dst).await;
+        }
+        let (archive_path, _archive_lock) = self
+            .download_archive_to_cache(&info.version, &archive)
+            .await?;
+        self.extract_archive(archive_path, dst.to_owned(), archive.format)
+            .await?;
+
+        if let Some(max_size) = self.config.get().await.cache.max_size {
+            for entry in cache::enforce_max_size(max_size.0).await? {
+                tracing::info!(
+                    "Removed {} from the cache to stay below {max_size}",
+                    entry.name
+                );
+            }
+        }
 
         Ok(())
     }
 
+    /// Extracts the archive into the given directory while it is downloaded
+    /// without storing it in the cache. The checksum is computed on the fly
+    /// and the extracted files must be discarded when it doesn't match.
     #[tracing::instrument(level = "debug", skip(self))]
-    fn extract_archive(&self, version: &SimpleVersion, archive_path: &Path) -> Result<()> {
-        let dst_path = NODE_VERSIONS_DIR.join(version.to_string());
-        extract::extract_file(archive_path, &dst_path)?;
+    async fn stream_archive(
+        &self,
+        version: &SimpleVersion,
+        archive: &ReleaseArchive,
+        dst: &Path,
+    ) -> Result<()> {
+        let res = self
+            .fetch(
+                version.channel(),
+                &format!("v{version}/{}", archive.file_name),
+                0,
+            )
+            .await
+            .context("Downloading nodejs")?;
+        let (writer, mut reader) = stream::pipe();
+        let progress = self.progress.clone();
+        let (name, dst_path, format) = (
+            PathBuf::from(&archive.file_name),
+            dst.to_owned(),
+            archive.format,
+        );
+        let mut extraction = tokio::task::spawn_blocking(move || {
+            let result = extract::extract_stream(&mut reader, &name, &dst_path, format, &progress);
+            (result, reader.reached_end())
+        });
+        let download = async move {
+            let mut writer = HashingWriter::new(writer, ChecksumHasher::default());
+            self.download_archive(format!("node v{version}"), res, &mut writer, 0)
+                .await?;
+            // dropping the writer ends the archive for the extraction
+            Ok::<_, miette::Report>(writer.finish())
+        };
+
+        let actual = tokio::select! {
+            // the extraction only finishes before the download when it failed
+            extracted = &mut extraction => {
+                extracted.into_diagnostic().context("Extracting archive")?.0?;
+                return Err(miette!("The extraction finished before the download"));
+            }
+            downloaded = download => {
+                let extracted = extraction.await.into_diagnostic().context("Extracting archive")?;
+
+                match (downloaded, extracted) {
+                    (Ok(actual), (Ok(_), _)) => actual,
+                    // the download fails as well when the extraction closes the pipe early
+                    // but the extraction only fails because of the download when it reached the end
+                    (_, (Err(e), false)) => return Err(e.into()),
+                    (Err(e), _) => return Err(e),
+                    (Ok(_), (Err(e), true)) => return Err(e.into()),
+                }
+            }
+        };
+
+        if actual != archive.checksum {
+            return Err(ChecksumMismatchError {
+                file_name: archive.file_name.to_owned(),
+                expected: archive.checksum.to_owned(),
+                actual,
+            }
+            .into());
+        }
 
         Ok(())
     }
 
+    /// Downloads the archive of the given version into the cache even if
+    /// archives aren't kept and returns the paths of all cached files of the release.
+    /// The cache must be locked and the returned lock held while the files are used.
     #[tracing::instrument(level = "debug", skip(self))]
-    async fn download_archive_to_cache(&self, version: &SimpleVersion) -> Result<PathBuf> {
-        let download_path = CACHE_DIR.join(format!("node-v{}{}", version, *NODE_ARCHIVE_SUFFIX));
+    pub async fn cache_release(&self, info: &VersionMetadata) -> Result<(Vec<PathBuf>, FileLock)> {
+        let archive = self.release_archive(info).await?;
+        let (archive_path, lock) = self
+            .download_archive_to_cache(&info.version, &archive)
+            .await?;
+        let flavor = self.flavor().await;
+        let release_files = cache::RELEASE_FILES
+            .iter()
+            .map(|name| CACHE_DIR.join(release_file_name(&info.version, flavor, name)))
+            .filter(|path| path.exists());
+
+        Ok((
+            std::iter::once(archive_path).chain(release_files).collect(),
+            lock,
+        ))
+    }
+
+    /// Fetches the index of the versions published in the given channel
+    /// with all fields as they are served by the mirror
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn fetch_index(&self, channel: Channel) -> Result<Vec<serde_json::Value>> {
+        let contents = self
+            .fetch(channel, "index.json", 0)
+            .await
+            .context("Fetching versions")?
+            .bytes()
+            .await
+            .context("Reading versions response")?;
+
+        serde_json::from_slice(&contents)
+            .into_diagnostic()
+            .context("Parsing versions response")
+    }
+
+    /// Adds the given versions to the cached list of versions of the channel
+    /// so that they can be resolved without fetching the list
+    #[tracing::instrument(level = "debug", skip(self, versions))]
+    pub async fn import_versions(
+        &mut self,
+        channel: Channel,
+        versions: Vec<VersionInfo>,
+    ) -> Result<()> {
+        let flavor = self.flavor().await;
+        let mut index = match self.versions.remove(&channel) {
+            Some(v) => v,
+            None => Versions::load(flavor, channel).await.unwrap_or_else(|| {
+                Versions::new(Vec::new(), flavor, channel, CacheValidators::default())
+            }),
+        };
+        index.merge(versions);
+        index.save().await?;
+        self.versions.insert(channel, index);
+
+        Ok(())
+    }
+
+    /// Extracts the archive on a blocking thread so that
+    /// multiple archives can be extracted in parallel
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn extract_archive(
+        &self,
+        archive_path: PathBuf,
+        dst_path: PathBuf,
+        format: ArchiveFormat,
+    ) -> Result<()> {
+        let progress = self.progress.clone();
+
+        tokio::task::spawn_blocking(move || {
+            extract::extract_file(&archive_path, &dst_path, format, &progress)
+        })
+        .await
+        .into_diagnostic()
+        .context("Extracting archive")??;
+
+        Ok(())
+    }
+
+    /// Chooses the archive to download for the given version
+    /// and looks up its published checksum
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn release_archive(&self, info: &VersionMetadata) -> Result<ReleaseArchive> {
+        let checksums = self.checksums(&info.version).await?;
+        let flavor = self.flavor().await;
+        let format = self.archive_format(info, flavor, &checksums).await?;
+        let file_name = archive_file_name(&info.version, format, flavor);
+        let checksum = checksums
+            .get(&file_name)
+            .ok_or_else(|| MissingChecksumError {
+                file_name: file_name.to_owned(),
+            })?
+            .to_owned();
+
+        Ok(ReleaseArchive {
+            file_name,
+            format,
+            checksum,
+        })
+    }
+
+    /// Downloads the archive of the given version into the cache
+    /// and returns its path. The archive isn't evicted from the cache
+    /// while the returned lock is held.
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn download_archive_to_cache(
+        &self,
+        version: &SimpleVersion,
+        archive: &ReleaseArchive,
+    ) -> Result<(PathBuf, FileLock)> {
+        let file_name = &archive.file_name;
+        let expected = &archive.checksum;
+        let lock = FileLock::exclusive(
+            &download_lock_name(file_name),
+            format!("downloading {file_name}"),
+        )
+        .await?;
+        let download_path = CACHE_DIR.join(file_name);
+        let part_path = CACHE_DIR.join(format!("{file_name}.part"));
 
         if download_path.exists() {
-            return Ok(download_path);
+            if file_checksum(&download_path).await? == *expected {
+                if let Err(e) = cache::touch(&download_path).await {
+                    tracing::warn!("Failed to mark {file_name} as used: {e}");
+                }
+                return Ok((download_path, lock));
+            }
+            tracing::warn!("Cached archive {download_path:?} is corrupted. Downloading it again.");
+            fs::remove_file(&download_path)
+                .await
+                .into_diagnostic()
+                .context("Removing corrupted archive from cache")?;
+        }
+
+        // a complete download that didn't get renamed
+        if part_path.exists() && file_checksum(&part_path).await? == *expected {
+            fs::rename(&part_path, &download_path)
+                .await
+                .into_diagnostic()
+                .context("Moving downloaded archive into the cache")?;
+            return Ok((download_path, lock));
+        }
+        let mut offset = if part_path.exists() {
+            fs::metadata(&part_path).await.into_diagnostic()?.len()
+        } else {
+            0
+        };
+        let archive_path = format!("v{version}/{file_name}");
+        let channel = version.channel();
+        let res = match self.fetch(channel, &archive_path, offset).await {
+            Err(e) if offset > 0 && e.status() == Some(StatusCode::RANGE_NOT_SATISFIABLE) => {
+                offset = 0;
+                self.fetch(channel, &archive_path, 0).await
+            }
+            res => res,
+        }
+        .context("Downloading nodejs")?;
+
+        if offset > 0 && !res.partial {
+            tracing::debug!("Mirror can't resume the download. Starting from the beginning.");
+            offset = 0;
+        }
+        let mut hasher = ChecksumHasher::default();
+
+        let file = if offset > 0 {
+            hasher.update_from_file(&part_path).await?;
+            OpenOptions::new()
+                .append(true)
+                .open(&part_path)
+                .await
+                .into_diagnostic()
+                .context("Opening partial download")?
+        } else {
+            File::create(&part_path)
+                .await
+                .into_diagnostic()
+                .context("Creating download file")?
+        };
+        let mut download_writer = HashingWriter::new(BufWriter::new(file), hasher);
+        self.download_archive(
+            format!("node v{version}"),
+            res,
+            &mut download_writer,
+            offset,
+        )
+        .await?;
+        let actual = download_writer.finish();
+
+        if actual != *expected {
+            fs::remove_file(&part_path)
+                .await
+                .into_diagnostic()
+                .context("Removing corrupted archive from cache")?;
+
+            return Err(ChecksumMismatchError {
+                file_name: file_name.to_owned(),
+                expected: expected.to_owned(),
+                actual,
+            }
+            .into());
+        }
+        fs::rename(&part_path, &download_path)
+            .await
+            .into_diagnostic()
+            .context("Moving downloaded archive into the cache")?;
+
+        Ok((download_path, lock))
+    }
+
+    /// Chooses the archive format to download for the given version.
+    /// The configured format is used if set, otherwise the preferred format
+    /// that is listed in the version index and has a published checksum.
+    async fn archive_format(
+        &self,
+        info: &VersionMetadata,
+        flavor: Flavor,
+        checksums: &Checksums,
+    ) -> Result<ArchiveFormat> {
+        let formats = match self.config.get().await.download.archive_format {
+            Some(format) => vec![format],
+            None => supported_archive_formats(),
+        };
+        let is_listed = |format: ArchiveFormat| {
+            // versions from the cache of older releases don't have a list of files
+            info.files.is_empty() || info.files.contains(&index_file_key(format, flavor))
+        };
+
+        formats
+            .iter()
+            .copied()
+            .find(|f| {
+                is_listed(*f)
+                    && checksums
+                        .get(archive_file_name(&info.version, *f, flavor))
+                        .is_some()
+            })
+            .ok_or_else(|| {
+                UnavailableArchiveError {
+                    version: info.version.to_string(),
+                    formats: formats
+                        .iter()
+                        .map(ArchiveFormat::to_string)
+                        .collect::<Vec<_>>()
+                        .join(" or "),
+                    platform: format!("{OS}-{ARCH}{}", flavor.suffix()),
+                }
+                .into()
+            })
+    }
+
+    /// Fetches the published checksums for the given version
+    /// and verifies their signature
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn checksums(&self, version: &SimpleVersion) -> Result<Checksums> {
+        let contents = self.fetch_release_file(version, "SHASUMS256.txt").await?;
+        self.verify_checksums_signature(version, &contents).await?;
+
+        Ok(Checksums::parse(&String::from_utf8_lossy(&contents)))
+    }
+
+    /// Verifies the signature of the checksums and handles a failed
+    /// verification according to the configured signature policy
+    #[tracing::instrument(level = "debug", skip(self, contents))]
+    async fn verify_checksums_signature(
+        &self,
+        version: &SimpleVersion,
+        contents: &[u8],
+    ) -> Result<()> {
+        let policy = self.config.get().await.download.signature_policy;
+
+        match self.check_checksums_signature(version, contents).await {
+            Ok(fingerprint) => {
+                tracing::debug!("Checksums for v{version} are signed by {fingerprint}");
+                Ok(())
+            }
+            Err(e) if policy == SignaturePolicy::Warn => {
+                self.progress
+                    .suspend(|| eprintln!("{} {e}", "Warning:".yellow().bold()));
+                Ok(())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn check_checksums_signature(
+        &self,
+        version: &SimpleVersion,
+        contents: &[u8],
+    ) -> Result<String, SignatureVerificationError> {
+        let error = |reason: String| SignatureVerificationError {
+            version: version.to_string(),
+            reason,
+        };
+        let signature = match self.fetch_release_file(version, "SHASUMS256.txt.sig").await {
+            Ok(s) => s,
+            Err(_) => self
+                .fetch_release_file(version, "SHASUMS256.txt.asc")
+                .await
+                .map_err(|_| error(String::from("no signature has been published")))?,
+        };
+        let signature = ChecksumSignature::parse(&signature)
+            .map_err(|e| error(format!("invalid signature file: {e}")))?;
+        let keyring = Keyring::load()
+            .await
+            .map_err(|e| error(format!("failed to load keyring: {e}")))?;
+
+        keyring
+            .verify(&signature, contents)
+            .map(|k| k.fingerprint())
+            .ok_or_else(|| error(String::from("it wasn't created by any known release key")))
+    }
+
+    /// Fetches a file from the dist directory of the given version.
+    /// The file is cached next to the downloaded archives.
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn fetch_release_file(&self, version: &SimpleVersion, name: &str) -> Result<Vec<u8>> {
+        let cache_path = CACHE_DIR.join(release_file_name(version, self.flavor().await, name));
+
+        if cache_path.exists() {
+            return fs::read(&cache_path)
+                .await
+                .into_diagnostic()
+                .with_context(|| format!("Reading cached {name}"));
+        }
+        let contents = self
+            .fetch(version.channel(), &format!("v{version}/{name}"), 0)
+            .await
+            .with_context(|| format!("Fetching {name}"))?
+            .bytes()
+            .await
+            .with_context(|| format!("Reading {name}"))?;
+        fs::write(&cache_path, &contents)
+            .await
+            .into_diagnostic()
+            .with_context(|| format!("Caching {name}"))?;
+
+        Ok(contents)
+    }
+
+    /// Requests a file of the given channel from the first available mirror.
+    /// If an offset is given only the remaining bytes are requested.
+    async fn fetch(
+        &self,
+        channel: Channel,
+        path: &str,
+        offset: u64,
+    ) -> Result<MirrorResponse, FetchError> {
+        self.request(channel, path, offset, None).await
+    }
+
+    /// Requests a file from the first available mirror.
+    /// If validators of a cached copy are given, the content
+    /// is only returned when the file has been modified.
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn request(
+        &self,
+        channel: Channel,
+        path: &str,
+        offset: u64,
+        validators: Option<&CacheValidators>,
+    ) -> Result<MirrorResponse, FetchError> {
+        let mut mirrors = self.mirrors(channel).await;
+
+        if self.offline {
+            mirrors.retain(Mirror::is_local);
+
+            if mirrors.is_empty() {
+                return Err(FetchError::Offline {
+                    path: path.to_owned(),
+                });
+            }
+        }
+        let mut mirrors = mirrors.iter().peekable();
+
+        while let Some(mirror) = mirrors.next() {
+            let mut attempt = 0;
+
+            loop {
+                match mirror.request(&self.client, path, offset, validators).await {
+                    Ok(res) => {
+                        tracing::info!("Fetching {path} from {mirror}");
+                        return Ok(res);
+                    }
+                    Err(e) if e.is_mirror_unavailable() && attempt < self.client.retries() => {
+                        let backoff = self.client.backoff(attempt);
+                        tracing::debug!("Retrying request to {mirror} in {backoff:?}: {e}");
+                        tokio::time::sleep(backoff).await;
+                        attempt += 1;
+                    }
+                    Err(e) if e.is_mirror_unavailable() && mirrors.peek().is_some() => {
+                        self.progress.suspend(|| {
+                            eprintln!(
+                                "{} Mirror {mirror} is unavailable ({e}). Trying the next one.",
+                                "Warning:".yellow().bold()
+                            )
+                        });
+                        break;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
         }
-        let mut download_writer =
-            BufWriter::new(File::create(&download_path).await.into_diagnostic()?);
-        self.download_archive(version.to_string(), &mut download_writer)
+        xkcd_unreachable!()
+    }
+
+    /// Downloads the file at the given url to the given path.
+    /// The file is requested directly without using the mirrors.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn download_url(&self, url: &str, dst: &Path) -> Result<()> {
+        let (file_name, res) = self.request_url(url, None).await?;
+        let file = File::create(dst)
+            .await
+            .into_diagnostic()
+            .context("Creating download file")?;
+        let mut writer = BufWriter::new(file);
+        self.download_archive(file_name, res, &mut writer, 0)
             .await?;
 
-        Ok(download_path)
+        Ok(())
+    }
+
+    /// Requests the file at the given url directly without using the mirrors
+    /// and returns its name and the response.
+    /// If validators of a cached copy are given, the content
+    /// is only returned when the file has been modified.
+    async fn request_url(
+        &self,
+        url: &str,
+        validators: Option<&CacheValidators>,
+    ) -> Result<(String, MirrorResponse)> {
+        let invalid_url = || miette!("Invalid url `{url}`");
+        let mut base_url = Url::parse(url).map_err(|_| invalid_url())?;
+        let file_name = base_url
+            .path_segments()
+            .and_then(|mut s| s.next_back())
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .ok_or_else(invalid_url)?;
+        let path = match base_url.query() {
+            Some(query) => format!("{file_name}?{query}"),
+            None => file_name.to_owned(),
+        };
+        base_url.set_query(None);
+        base_url.set_fragment(None);
+        base_url
+            .path_segments_mut()
+            .map_err(|_| invalid_url())?
+            .pop();
+        let mirror = Mirror::new(base_url, None);
+
+        if self.offline && !mirror.is_local() {
+            return Err(FetchError::Offline {
+                path: url.to_owned(),
+            }
+            .into());
+        }
+        let res = mirror
+            .request(&self.client, &path, 0, validators)
+            .await
+            .with_context(|| format!("Downloading {file_name}"))?;
+
+        Ok((file_name, res))
     }
-    /// Downloads a specific node version
-    /// and writes it to the given writer
-    #[tracing::instrument(level = "debug", skip(self, writer))]
+
+    /// Requests the versions index from each mirror and
+    /// returns the time it took to respond
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn test_mirrors(&self) -> Vec<(Mirror, Result<Duration>)> {
+        let mirrors = self.mirrors(Channel::Release).await;
+
+        future::join_all(mirrors.into_iter().map(|mirror| async move {
+            let start = Instant::now();
+            let result = async {
+                mirror
+                    .get(&self.client, "index.json", 0)
+                    .await?
+                    .bytes()
+                    .await?;
+                Ok(start.elapsed())
+            }
+            .await;
+            (mirror, result)
+        }))
+        .await
+    }
+
+    /// Downloads a file from the given response and writes it to the given writer.
+    /// The offset is the number of bytes that have already been downloaded before.
+    #[tracing::instrument(level = "debug", skip(self, res, writer))]
     pub async fn download_archive<W: AsyncWrite + Unpin, S: Display + Debug>(
         &self,
-        version: S,
+        name: S,
+        res: MirrorResponse,
         writer: &mut W,
+        offset: u64,
     ) -> Result<u64> {
-        let res = reqwest::get(format!(
-            "{}/v{version}/node-v{version}{}",
-            self.base_url().await,
-            *NODE_ARCHIVE_SUFFIX
-        ))
-        .await
-        .map_err(ReqwestError::from)
-        .context("Downloading nodejs")?;
-
-        let total_size = res
-            .content_length()
-            .ok_or_else(|| miette!("Missing content_length header"))?;
-
-        let pb = progress_bar(total_size);
-        pb.set_message(format!("Downloading node v{version}"));
+        let pb = if let Some(remaining) = res.content_length {
+            let pb = self.progress.add(progress_bar(remaining + offset));
+            pb.set_position(offset);
+            pb
+        } else {
+            self.progress.add(progress_bytes_spinner())
+        };
+        pb.set_message(format!("Downloading {name}"));
         let mut stream = res.bytes_stream();
-        let mut total_downloaded = 0;
+        let mut total_downloaded = offset;
 
         while let Some(item) = stream.next().await {
-            let chunk = item.map_err(ReqwestError::from)?;
+            let chunk = item?;
             writer
                 .write_all(&chunk)
                 .await
                 .into_diagnostic()
                 .context("Writing download chunk to file")?;
-            total_downloaded = min(chunk.len() as u64 + total_downloaded, total_size);
+            total_downloaded += chunk.len() as u64;
             pb.set_position(total_downloaded);
         }
 
         writer.flush().await.into_diagnostic()?;
-        pb.finish_with_message(format!("Downloaded node v{version}."));
+        pb.finish_with_message(format!("Downloaded {name}."));
 
         Ok(total_downloaded)
     }
 
-    async fn base_url(&self) -> String {
-        self.config.get().await.download.dist_base_url.to_owned()
+    /// Returns the configured mirrors in the order they should be tried.
+    /// Unofficial builds are always downloaded from the mirrors of the unofficial builds directory
+    /// and prereleases from the directory of their channel.
+    async fn mirrors(&self, channel: Channel) -> Vec<Mirror> {
+        let config = self.config.get().await;
+        let download = &config.download;
+
+        if !channel.is_release() {
+            configured_mirrors(&download.channel_mirrors, &download.channels_base_url)
+                .into_iter()
+                .map(|m| m.join(&channel.to_string()))
+                .collect()
+        } else if !download.flavor.is_official() {
+            configured_mirrors(&download.unofficial_mirrors, &download.unofficial_base_url)
+        } else {
+            configured_mirrors(&download.mirrors, &download.dist_base_url)
+        }
+    }
+
+    /// Returns the configured build flavor
+    async fn flavor(&self) -> Flavor {
+        self.config.get().await.download.flavor
+    }
+}
+
+/// Acquires the shared lock on the download cache that
+/// prevents it from being cleared while it is used
+pub(crate) async fn lock_cache() -> Result<FileLock> {
+    FileLock::shared("cache", "clearing the download cache").await
+}
+
+/// Returns the configured mirrors or a mirror of the default
+/// base url when none are configured
+fn configured_mirrors(mirrors: &[MirrorConfig], base_url: &str) -> Vec<Mirror> {
+    if mirrors.is_empty() {
+        vec![Mirror::new(base_url, None)]
+    } else {
+        mirrors
+            .iter()
+            .map(|m| Mirror::new(&m.url, m.auth.to_owned()))
+            .collect()
+    }
+}
+
+/// Returns the name of the lock that is held while an archive
+/// is downloaded or used and prevents it from being evicted
+fn download_lock_name(file_name: &str) -> String {
+    format!("download-{file_name}")
+}
+
+/// Returns the name a file from the dist directory of the given version is cached as.
+/// Files of unofficial builds are cached separately as they differ from the official ones.
+fn release_file_name(version: &SimpleVersion, flavor: Flavor, name: &str) -> String {
+    if flavor.is_official() {
+        format!("node-v{version}-{name}")
+    } else {
+        format!("node-v{version}-{flavor}-{name}")
+    }
+}
+
+fn archive_file_name(version: &SimpleVersion, format: ArchiveFormat, flavor: Flavor) -> String {
+    format!(
+        "node-v{version}-{OS}-{ARCH}{}.{}",
+        flavor.suffix(),
+        format.extension()
+    )
+}
+
+/// Returns the archive formats that can be installed on this platform
+/// ordered by preference
+pub fn supported_archive_formats() -> Vec<ArchiveFormat> {
+    if cfg!(target_os = "windows") {
+        vec![ArchiveFormat::Zip]
+    } else {
+        vec![ArchiveFormat::TarXz, ArchiveFormat::TarGz]
+    }
+}
+
+/// Returns the entry in the files list of the version index
+/// that indicates that archives of the given format are published
+pub fn index_file_key(format: ArchiveFormat, flavor: Flavor) -> String {
+    match format {
+        ArchiveFormat::Zip => format!("win-{ARCH}-zip"),
+        _ if OS == "darwin" => format!("osx-{ARCH}-tar"),
+        _ => format!("{OS}-{ARCH}{}", flavor.suffix()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Once;
+
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    const ARCHIVE: &[u8] = b"the contents of a node archive";
+
+    /// Points the nenv directories to a temporary directory
+    /// so that the tests don't touch the real cache
+    fn use_test_dirs() {
+        static INIT: Once = Once::new();
+
+        INIT.call_once(|| {
+            let dir = std::env::temp_dir().join(format!("nenv-test-dirs-{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::env::set_var("XDG_CACHE_HOME", dir.join("cache"));
+            std::env::set_var("XDG_DATA_HOME", dir.join("data"));
+            std::env::set_var("XDG_CONFIG_HOME", dir.join("config"));
+            std::fs::create_dir_all(&*CACHE_DIR).unwrap();
+        });
+    }
+
+    async fn downloader(mirrors: &[&MockServer]) -> NodeDownloader {
+        use_test_dirs();
+        let config = ConfigAccess::load().await.unwrap();
+        {
+            let mut config = config.get_mut().await;
+            config.download.mirrors = mirrors
+                .iter()
+                .map(|s| MirrorConfig {
+                    url: s.uri(),
+                    auth: None,
+                })
+                .collect();
+            config.download.http.retries = 1;
+        }
+
+        NodeDownloader::new(config, false).await.unwrap()
+    }
+
+    async fn respond(server: &MockServer, file: &str, response: ResponseTemplate) {
+        Mock::given(method("GET"))
+            .and(path(file))
+            .respond_with(response)
+            .mount(server)
+            .await;
+    }
+
+    fn release_archive(version: &str) -> (SimpleVersion, ReleaseArchive) {
+        let version = semver::Version::parse(version).unwrap();
+        let mut hasher = ChecksumHasher::default();
+        hasher.update(ARCHIVE);
+        let archive = ReleaseArchive {
+            file_name: format!("node-v{version}-test.tar.gz"),
+            format: ArchiveFormat::TarGz,
+            checksum: hasher.finish(),
+        };
+
+        (version.into(), archive)
+    }
+
+    fn archive_path(version: &SimpleVersion, archive: &ReleaseArchive) -> String {
+        format!("/v{version}/{}", archive.file_name)
+    }
+
+    #[test]
+    fn it_only_treats_server_errors_as_unavailable_mirrors() {
+        let status = |status| FetchError::Status {
+            url: String::from("https://example.com"),
+            status,
+        };
+
+        assert!(status(StatusCode::SERVICE_UNAVAILABLE).is_mirror_unavailable());
+        assert!(status(StatusCode::BAD_GATEWAY).is_mirror_unavailable());
+        assert!(!status(StatusCode::NOT_FOUND).is_mirror_unavailable());
+        assert!(!status(StatusCode::RANGE_NOT_SATISFIABLE).is_mirror_unavailable());
+        assert!(FetchError::Timeout {
+            url: String::from("https://example.com")
+        }
+        .is_mirror_unavailable());
+        assert!(!FetchError::Auth {
+            url: String::from("https://example.com"),
+            reason: String::from("missing token"),
+        }
+        .is_mirror_unavailable());
+    }
+
+    #[tokio::test]
+    async fn it_retries_and_fails_over_to_the_next_mirror() {
+        let unavailable = MockServer::start().await;
+        let available = MockServer::start().await;
+        respond(&unavailable, "/index.json", ResponseTemplate::new(503)).await;
+        respond(
+            &available,
+            "/index.json",
+            ResponseTemplate::new(200).set_body_string("[]"),
+        )
+        .await;
+
+        let res = downloader(&[&unavailable, &available])
+            .await
+            .fetch(Channel::Release, "index.json", 0)
+            .await
+            .unwrap();
+
+        assert_eq!(res.bytes().await.unwrap(), b"[]");
+        assert_eq!(unavailable.received_requests().await.unwrap().len(), 2);
+        assert_eq!(available.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn it_doesnt_fail_over_on_client_errors() {
+        let missing = MockServer::start().await;
+        let available = MockServer::start().await;
+        respond(&missing, "/index.json", ResponseTemplate::new(404)).await;
+        respond(&available, "/index.json", ResponseTemplate::new(200)).await;
+
+        let res = downloader(&[&missing, &available])
+            .await
+            .fetch(Channel::Release, "index.json", 0)
+            .await;
+
+        assert_eq!(res.err().unwrap().status(), Some(StatusCode::NOT_FOUND));
+        assert_eq!(missing.received_requests().await.unwrap().len(), 1);
+        assert!(available.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_resumes_partial_downloads() {
+        let server = MockServer::start().await;
+        let (version, archive) = release_archive("20.0.1");
+        let downloader = downloader(&[&server]).await;
+        let part_path = CACHE_DIR.join(format!("{}.part", archive.file_name));
+        fs::write(&part_path, &ARCHIVE[..10]).await.unwrap();
+        Mock::given(method("GET"))
+            .and(path(archive_path(&version, &archive)))
+            .and(header("range", "bytes=10-"))
+            .respond_with(ResponseTemplate::new(206).set_body_bytes(&ARCHIVE[10..]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let (path, _lock) = downloader
+            .download_archive_to_cache(&version, &archive)
+            .await
+            .unwrap();
+
+        assert_eq!(fs::read(&path).await.unwrap(), ARCHIVE);
+        assert!(!part_path.exists());
+    }
+
+    #[tokio::test]
+    async fn it_restarts_downloads_that_cant_be_resumed() {
+        let server = MockServer::start().await;
+        let (version, archive) = release_archive("20.0.2");
+        let downloader = downloader(&[&server]).await;
+        // a stale partial download that is longer than the archive
+        let part_path = CACHE_DIR.join(format!("{}.part", archive.file_name));
+        fs::write(&part_path, [ARCHIVE, ARCHIVE].concat())
+            .await
+            .unwrap();
+        Mock::given(method("GET"))
+            .and(path(archive_path(&version, &archive)))
+            .and(header(
+                "range",
+                format!("bytes={}-", ARCHIVE.len() * 2).as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(416))
+            .with_priority(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        respond(
+            &server,
+            &archive_path(&version, &archive),
+            ResponseTemplate::new(200).set_body_bytes(ARCHIVE),
+        )
+        .await;
+
+        let (path, _lock) = downloader
+            .download_archive_to_cache(&version, &archive)
+            .await
+            .unwrap();
+
+        assert_eq!(fs::read(&path).await.unwrap(), ARCHIVE);
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn it_restarts_downloads_when_the_mirror_ignores_the_range() {
+        let server = MockServer::start().await;
+        let (version, archive) = release_archive("20.0.3");
+        let downloader = downloader(&[&server]).await;
+        let part_path = CACHE_DIR.join(format!("{}.part", archive.file_name));
+        fs::write(&part_path, b"corrupted").await.unwrap();
+        respond(
+            &server,
+            &archive_path(&version, &archive),
+            ResponseTemplate::new(200).set_body_bytes(ARCHIVE),
+        )
+        .await;
+
+        let (path, _lock) = downloader
+            .download_archive_to_cache(&version, &archive)
+            .await
+            .unwrap();
+
+        assert_eq!(fs::read(&path).await.unwrap(), ARCHIVE);
+    }
+
+    #[tokio::test]
+    async fn it_refuses_versions_that_reached_their_end_of_life() {
+        let server = MockServer::start().await;
+        respond(
+            &server,
+            "/schedule.json",
+            ResponseTemplate::new(200).set_body_string(
+                r#"{
+                    "v18": { "start": "2022-04-19", "end": "2025-04-30" },
+                    "v99": { "start": "2022-04-19", "end": "2999-12-31" }
+                }"#,
+            ),
+        )
+        .await;
+        let downloader = downloader(&[]).await;
+        {
+            let mut config = downloader.config.get_mut().await;
+            config.download.schedule_url = format!("{}/schedule.json?ref=main", server.uri());
+            config.node.eol_policy = EolPolicy::Refuse;
+        }
+        let version = |v| SimpleVersion::from(semver::Version::parse(v).unwrap());
+
+        let err = downloader
+            .check_end_of_life(&version("18.20.0"))
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<EndOfLifeError>().map(|e| e.end.as_str()),
+            Some("2025-04-30")
+        );
+        assert!(downloader
+            .check_end_of_life(&version("99.0.0"))
+            .await
+            .is_ok());
+        assert!(!server.received_requests().await.unwrap().is_empty());
     }
 }
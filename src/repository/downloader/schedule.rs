@@ -0,0 +1,260 @@
+use std::{collections::HashMap, fmt, path::PathBuf, time::Duration};
+
+use miette::{Context, IntoDiagnostic, Result};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::{
+    consts::CACHE_DIR,
+    error::SerializeBincodeError,
+    lock::{write_atomic, FileLock},
+    utils::{deserialize_cache, serialize_cache, today, unix_now},
+    versioning::SimpleVersion,
+};
+
+use super::mirror::CacheValidators;
+
+/// The copy of the release schedule shipped with nenv
+const BUNDLED_SCHEDULE: &str = include_str!("../../../schedule/schedule.json");
+
+/// The dates of the support phases of each major version
+/// as published in the `schedule.json` of the nodejs release working group
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReleaseSchedule {
+    majors: HashMap<u8, MajorSchedule>,
+    /// The unix timestamp of when the schedule was fetched or last revalidated
+    fetched_at: u64,
+    validators: CacheValidators,
+    /// Whether this is the copy bundled with nenv because the configured one is unavailable.
+    /// The dates of a cached bundled copy are replaced with the ones of the running nenv
+    /// so that a newer bundled copy is used after an update.
+    bundled: bool,
+}
+
+/// The dates of a major version like `2024-01-31`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MajorSchedule {
+    pub start: String,
+    /// The start of the active lts phase
+    #[serde(default)]
+    pub lts: Option<String>,
+    #[serde(default)]
+    pub maintenance: Option<String>,
+    /// The end of life after which no more releases are published
+    pub end: String,
+    #[serde(default)]
+    pub codename: Option<String>,
+}
+
+/// The support phase a major version is in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReleasePhase {
+    /// The newest major version that receives all changes
+    Current,
+    /// A lts version that receives new features and fixes
+    ActiveLts,
+    /// Only critical bug fixes and security updates are released
+    Maintenance,
+    /// No more releases are published
+    EndOfLife,
+}
+
+impl ReleaseSchedule {
+    /// Loads the cached release schedule
+    pub(crate) async fn load() -> Option<Self> {
+        if !cache_path().exists() {
+            return None;
+        }
+        let byte_contents = fs::read(cache_path()).await.ok()?;
+
+        match deserialize_cache::<Self>(&byte_contents) {
+            Ok(schedule) if schedule.bundled => Some(Self {
+                fetched_at: schedule.fetched_at,
+                ..Self::bundled()
+            }),
+            Ok(schedule) => Some(schedule),
+            Err(e) => {
+                tracing::error!("Failed to deserialize cache {e}");
+                fs::remove_file(cache_path()).await.ok()?;
+                None
+            }
+        }
+    }
+
+    /// Returns the schedule shipped with nenv
+    pub fn bundled() -> Self {
+        let schedule = Self::parse(BUNDLED_SCHEDULE.as_bytes(), CacheValidators::default())
+            .expect("the bundled release schedule is valid");
+
+        Self {
+            bundled: true,
+            ..schedule
+        }
+    }
+
+    /// Parses the contents of a `schedule.json`.
+    /// Major versions before 1.0 are skipped.
+    pub fn parse(contents: &[u8], validators: CacheValidators) -> Result<Self> {
+        let entries: HashMap<String, MajorSchedule> = serde_json::from_slice(contents)
+            .into_diagnostic()
+            .context("Parsing release schedule")?;
+        let majors = entries
+            .into_iter()
+            .filter_map(|(version, schedule)| {
+                let major = version.trim_start_matches('v').parse().ok()?;
+                Some((major, schedule))
+            })
+            .collect();
+
+        Ok(Self {
+            majors,
+            fetched_at: unix_now(),
+            validators,
+            bundled: false,
+        })
+    }
+
+    /// Returns if the schedule is older than the given ttl and needs to be revalidated
+    pub fn is_expired(&self, ttl: Duration) -> bool {
+        unix_now().saturating_sub(self.fetched_at) >= ttl.as_secs()
+    }
+
+    /// Marks the schedule as up to date with the configured one
+    pub fn touch(&mut self) {
+        self.fetched_at = unix_now();
+    }
+
+    pub fn is_bundled(&self) -> bool {
+        self.bundled
+    }
+
+    pub fn validators(&self) -> &CacheValidators {
+        &self.validators
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) async fn save(&self) -> Result<()> {
+        let byte_content = serialize_cache(self).map_err(SerializeBincodeError::from)?;
+        let _lock = FileLock::exclusive("schedule", "updating the release schedule").await?;
+        write_atomic(&cache_path(), &byte_content).context("Caching release schedule.")?;
+
+        Ok(())
+    }
+
+    /// Returns the schedule of the major version of the given version
+    pub fn get(&self, version: &SimpleVersion) -> Option<&MajorSchedule> {
+        self.majors.get(&version.major)
+    }
+
+    /// Returns the codenames of all lts lines
+    pub fn codenames(&self) -> impl Iterator<Item = &String> {
+        self.majors.values().filter_map(|m| m.codename.as_ref())
+    }
+
+    /// Returns the phase the given version is in today.
+    /// Prereleases and versions that aren't released yet don't have a phase.
+    pub fn phase(&self, version: &SimpleVersion) -> Option<ReleasePhase> {
+        if !version.channel().is_release() {
+            return None;
+        }
+        self.get(version)?.phase(&today())
+    }
+
+    /// Returns the end of life date of the given version if it has been reached
+    pub fn end_of_life(&self, version: &SimpleVersion) -> Option<&str> {
+        if self.phase(version)? == ReleasePhase::EndOfLife {
+            Some(&self.get(version)?.end)
+        } else {
+            None
+        }
+    }
+}
+
+impl MajorSchedule {
+    /// Returns the phase of the major version on the given date
+    pub fn phase(&self, date: &str) -> Option<ReleasePhase> {
+        let reached = |d: &Option<String>| d.as_deref().is_some_and(|d| date >= d);
+
+        if date < self.start.as_str() {
+            None
+        } else if date > self.end.as_str() {
+            Some(ReleasePhase::EndOfLife)
+        } else if reached(&self.maintenance) {
+            Some(ReleasePhase::Maintenance)
+        } else if reached(&self.lts) {
+            Some(ReleasePhase::ActiveLts)
+        } else {
+            Some(ReleasePhase::Current)
+        }
+    }
+}
+
+impl fmt::Display for ReleasePhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReleasePhase::Current => "Current".fmt(f),
+            ReleasePhase::ActiveLts => "Active LTS".fmt(f),
+            ReleasePhase::Maintenance => "Maintenance".fmt(f),
+            ReleasePhase::EndOfLife => "EOL".fmt(f),
+        }
+    }
+}
+
+fn cache_path() -> PathBuf {
+    CACHE_DIR.join("schedule.cache")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEDULE: &str = r#"{
+        "v0.12": { "start": "2015-02-06", "maintenance": "2016-04-01", "end": "2016-12-31" },
+        "v18": {
+            "start": "2022-04-19",
+            "lts": "2022-10-25",
+            "maintenance": "2023-10-18",
+            "end": "2025-04-30",
+            "codename": "Hydrogen"
+        },
+        "v99": { "start": "2999-01-01", "end": "2999-12-31" }
+    }"#;
+
+    fn version(version: &str) -> SimpleVersion {
+        semver::Version::parse(version).unwrap().into()
+    }
+
+    #[test]
+    fn it_calculates_the_phase_on_a_date() {
+        let schedule =
+            ReleaseSchedule::parse(SCHEDULE.as_bytes(), CacheValidators::default()).unwrap();
+        let v18 = schedule.get(&version("18.0.0")).unwrap();
+
+        assert_eq!(v18.phase("2022-04-18"), None);
+        assert_eq!(v18.phase("2022-04-19"), Some(ReleasePhase::Current));
+        assert_eq!(v18.phase("2022-10-25"), Some(ReleasePhase::ActiveLts));
+        assert_eq!(v18.phase("2023-10-17"), Some(ReleasePhase::ActiveLts));
+        assert_eq!(v18.phase("2023-10-18"), Some(ReleasePhase::Maintenance));
+        assert_eq!(v18.phase("2025-04-30"), Some(ReleasePhase::Maintenance));
+        assert_eq!(v18.phase("2025-05-01"), Some(ReleasePhase::EndOfLife));
+    }
+
+    #[test]
+    fn it_returns_the_end_of_life_of_released_versions() {
+        let schedule =
+            ReleaseSchedule::parse(SCHEDULE.as_bytes(), CacheValidators::default()).unwrap();
+
+        assert_eq!(
+            schedule.end_of_life(&version("18.20.0")),
+            Some("2025-04-30")
+        );
+        assert_eq!(schedule.phase(&version("99.0.0")), None);
+        assert_eq!(schedule.end_of_life(&version("99.0.0")), None);
+        assert_eq!(
+            schedule.phase(&version("18.0.0-nightly20220101abcdef")),
+            None
+        );
+        // versions before 1.0 are not in the schedule
+        assert_eq!(schedule.phase(&version("0.12.0")), None);
+    }
+}
@@ -0,0 +1,47 @@
+use std::io;
+
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, DuplexStream},
+    runtime::Handle,
+};
+
+/// The number of bytes buffered between the download and the extraction
+const PIPE_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Reads from an async reader on a blocking thread
+/// so that the content can be passed to synchronous decoders
+pub struct BlockingReader<R> {
+    inner: R,
+    handle: Handle,
+    reached_end: bool,
+}
+
+impl<R> BlockingReader<R> {
+    /// Returns if the end of the content has been read
+    pub fn reached_end(&self) -> bool {
+        self.reached_end
+    }
+}
+
+impl<R: AsyncRead + Unpin> io::Read for BlockingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.handle.block_on(self.inner.read(buf))?;
+        self.reached_end |= read == 0 && !buf.is_empty();
+
+        Ok(read)
+    }
+}
+
+/// Creates a pipe whose writer is used by the download
+/// and whose reader is read by the extraction.
+/// The reader returns the end of the file once the writer is dropped.
+pub fn pipe() -> (DuplexStream, BlockingReader<DuplexStream>) {
+    let (writer, reader) = tokio::io::duplex(PIPE_BUFFER_SIZE);
+    let reader = BlockingReader {
+        inner: reader,
+        handle: Handle::current(),
+        reached_end: false,
+    };
+
+    (writer, reader)
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{cmp::Reverse, collections::HashMap, path::PathBuf, time::Duration};
 
 use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
@@ -6,18 +6,28 @@ use std::fmt::Debug;
 use tokio::fs;
 
 use crate::{
-    consts::VERSION_FILE_PATH,
+    config::Flavor,
+    consts::{CACHE_DIR, VERSION_FILE_PATH},
     error::SerializeBincodeError,
-    versioning::{SimpleVersion, VersionMetadata},
+    lock::{write_atomic, FileLock},
+    utils::{deserialize_cache, serialize_cache, unix_now},
+    versioning::{Channel, SimpleVersion, VersionMetadata},
 };
-use miette::{Context, IntoDiagnostic, Result};
+use miette::{Context, Result};
 
-use super::VersionInfo;
+use super::{index_file_key, mirror::CacheValidators, supported_archive_formats, VersionInfo};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Versions {
     lts_versions: HashMap<String, u8>,
     versions: HashMap<SimpleVersion, VersionMetadata>,
+    /// The flavor of the builds listed in the index
+    flavor: Flavor,
+    /// The release channel the index belongs to
+    channel: Channel,
+    /// The unix timestamp of when the index was fetched or last revalidated
+    fetched_at: u64,
+    validators: CacheValidators,
     // as this field is not serialized
     // it needs to be calculated after serialization
     #[serde(skip)]
@@ -25,14 +35,16 @@ pub struct Versions {
 }
 
 impl Versions {
-    /// Loads the versions from the cached versions.json file
-    pub(crate) async fn load() -> Option<Self> {
-        if !VERSION_FILE_PATH.exists() {
+    /// Loads the versions of the given flavor and channel from the cached versions.json file
+    pub(crate) async fn load(flavor: Flavor, channel: Channel) -> Option<Self> {
+        let path = cache_path(flavor, channel);
+
+        if !path.exists() {
             return None;
         }
-        let byte_contents = fs::read(&*VERSION_FILE_PATH).await.ok()?;
+        let byte_contents = fs::read(&path).await.ok()?;
 
-        match bincode::deserialize::<Versions>(&byte_contents) {
+        match deserialize_cache::<Versions>(&byte_contents) {
             Ok(mut versions) => {
                 // create the list of sorted versions
                 // this is faster when done directly rather than
@@ -42,7 +54,7 @@ impl Versions {
             }
             Err(e) => {
                 tracing::error!("Failed to deserialize cache {e}");
-                fs::remove_file(&*VERSION_FILE_PATH).await.ok()?;
+                fs::remove_file(&path).await.ok()?;
                 None
             }
         }
@@ -50,7 +62,12 @@ impl Versions {
 
     /// creates a new instance to access version information
     #[tracing::instrument(level = "debug", skip_all)]
-    pub fn new(all_versions: Vec<VersionInfo>) -> Self {
+    pub fn new(
+        all_versions: Vec<VersionInfo>,
+        flavor: Flavor,
+        channel: Channel,
+        validators: CacheValidators,
+    ) -> Self {
         let lts_versions = all_versions
             .iter()
             .filter_map(|v| Some((v.lts.lts_ref()?.to_lowercase(), v.version.major as u8)))
@@ -69,39 +86,87 @@ impl Versions {
         Self {
             lts_versions,
             versions,
+            flavor,
+            channel,
+            fetched_at: unix_now(),
+            validators,
             sorted_versions,
         }
     }
 
+    /// Adds the given versions to the index and replaces known ones
+    pub fn merge(&mut self, versions: Vec<VersionInfo>) {
+        for info in versions {
+            if let Some(lts) = info.lts.lts_ref() {
+                self.lts_versions
+                    .insert(lts.to_lowercase(), info.version.major as u8);
+            }
+            self.versions
+                .insert(info.version.to_owned().into(), info.into());
+        }
+        self.create_sorted_versions();
+    }
+
+    /// Returns the time since the index was fetched or last revalidated
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(unix_now().saturating_sub(self.fetched_at))
+    }
+
+    /// Returns if the index is older than the given ttl and needs to be revalidated
+    pub fn is_expired(&self, ttl: Duration) -> bool {
+        self.age() >= ttl
+    }
+
+    /// Marks the index as up to date with the mirror
+    pub fn touch(&mut self) {
+        self.fetched_at = unix_now();
+    }
+
+    pub fn validators(&self) -> &CacheValidators {
+        &self.validators
+    }
+
     #[tracing::instrument(level = "debug", skip_all)]
     pub(crate) async fn save(&self) -> Result<()> {
-        let byte_content = bincode::serialize(self).map_err(SerializeBincodeError::from)?;
-        fs::write(&*VERSION_FILE_PATH, byte_content)
-            .await
-            .into_diagnostic()
+        let byte_content = serialize_cache(self).map_err(SerializeBincodeError::from)?;
+        let _lock = FileLock::exclusive("versions", "updating the list of versions").await?;
+        write_atomic(&cache_path(self.flavor, self.channel), &byte_content)
             .context("Caching available node version.")?;
 
         Ok(())
     }
 
-    /// Returns the latest known node version
+    /// Returns the latest node version available for this platform
     #[tracing::instrument(level = "debug", skip_all)]
-    pub fn latest(&self) -> &VersionMetadata {
-        self.versions
-            .get(self.sorted_versions.last().expect("No known node versions"))
-            .unwrap()
+    pub fn latest(&self) -> Option<&VersionMetadata> {
+        self.available().next_back()
     }
 
-    /// Returns the latest node lts version
+    /// Returns the latest node lts version available for this platform
     #[tracing::instrument(level = "debug", skip_all)]
-    pub fn latest_lts(&self) -> &VersionMetadata {
-        let mut versions = self
-            .lts_versions
+    pub fn latest_lts(&self) -> Option<&VersionMetadata> {
+        self.lts_versions
             .values()
             .filter_map(|req| self.get_latest_for_major(*req))
+            .max_by_key(|v| &v.version)
+    }
+
+    /// Returns the names of all lts lines, newest first
+    pub fn lts_lines(&self) -> Vec<&str> {
+        let mut lines = self
+            .lts_versions
+            .iter()
+            .map(|(name, major)| (*major, name.as_str()))
             .collect::<Vec<_>>();
-        versions.sort_by_key(|v| &v.version);
-        versions.last().expect("No known lts node versions")
+        lines.sort_by_key(|(major, _)| Reverse(*major));
+
+        lines.into_iter().map(|(_, name)| name).collect()
+    }
+
+    /// Returns if a lts version with the given name is known
+    /// regardless of the platforms it is available for
+    pub fn is_lts<S: AsRef<str>>(&self, lts_name: S) -> bool {
+        self.lts_versions.contains_key(lts_name.as_ref())
     }
 
     /// Returns a lts version by name
@@ -111,18 +176,20 @@ impl Versions {
         self.get_latest_for_major(*lts_version)
     }
 
-    /// Returns any version that fulfills the given requirement
+    /// Returns the latest version available for this platform
+    /// that fulfills the given requirement.
+    /// Prereleases of channels fulfill the requirements of their plain version.
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn get_fulfilling(&self, req: &VersionReq) -> Option<&VersionMetadata> {
-        let fulfilling_versions = self
-            .sorted_versions
-            .iter()
-            .map(|v| (*v).into())
-            .filter(|v| req.matches(v))
-            .collect::<Vec<_>>();
+        self.available()
+            .rev()
+            .find(|v| v.version.matches_loosely(req))
+    }
 
-        let version = fulfilling_versions.last()?.clone().into();
-        self.versions.get(&version)
+    /// Returns if any known version fulfills the given requirement
+    /// regardless of the platforms it is available for
+    pub fn is_fulfillable(&self, req: &VersionReq) -> bool {
+        self.sorted_versions.iter().any(|v| v.matches_loosely(req))
     }
 
     /// Returns the info for the given version
@@ -131,17 +198,44 @@ impl Versions {
         self.versions.get(&version.clone().into())
     }
 
-    /// Returns any version that fulfills the given requirement
+    /// Returns all known versions sorted from oldest to newest
+    /// regardless of the platforms they are available for
+    pub fn all(&self) -> impl DoubleEndedIterator<Item = &VersionMetadata> {
+        self.sorted_versions
+            .iter()
+            .filter_map(|v| self.versions.get(v))
+    }
+
+    /// Returns the newest release with security fixes that has been published
+    /// after the given version in the same major version
+    pub fn security_update(&self, version: &SimpleVersion) -> Option<&VersionMetadata> {
+        self.all()
+            .rev()
+            .filter(|v| v.security && v.version.major == version.major)
+            .find(|v| v.version > *version)
+    }
+
+    /// Returns the latest version of the given major version available for this platform
     #[tracing::instrument(level = "debug", skip(self))]
     fn get_latest_for_major(&self, major: u8) -> Option<&VersionMetadata> {
-        let fulfilling_versions = self
-            .sorted_versions
+        self.available().rev().find(|v| v.version.major == major)
+    }
+
+    /// Returns the sorted versions that have a build for this platform
+    fn available(&self) -> impl DoubleEndedIterator<Item = &VersionMetadata> {
+        self.sorted_versions
             .iter()
-            .filter(|v| v.major == major)
-            .collect::<Vec<_>>();
+            .filter_map(|v| self.versions.get(v))
+            .filter(|v| self.has_platform_build(v))
+    }
 
-        let version = fulfilling_versions.last()?;
-        self.versions.get(version)
+    /// Returns if a build for the current platform is published for the version.
+    /// Versions without a list of files are assumed to be available.
+    pub fn has_platform_build(&self, version: &VersionMetadata) -> bool {
+        version.files.is_empty()
+            || supported_archive_formats()
+                .into_iter()
+                .any(|f| version.files.contains(&index_file_key(f, self.flavor)))
     }
 
     /// Creates the list of sorted versions
@@ -151,3 +245,14 @@ impl Versions {
         self.sorted_versions.sort();
     }
 }
+
+/// Returns the path of the cached index for the given flavor and channel
+fn cache_path(flavor: Flavor, channel: Channel) -> PathBuf {
+    if !channel.is_release() {
+        CACHE_DIR.join(format!("versions-{channel}.cache"))
+    } else if flavor.is_official() {
+        VERSION_FILE_PATH.to_owned()
+    } else {
+        CACHE_DIR.join(format!("versions-{flavor}.cache"))
+    }
+}
@@ -0,0 +1,169 @@
+use std::path::{Path, PathBuf};
+
+use miette::{IntoDiagnostic, Result};
+use reqwest::Url;
+use semver::Version;
+use tokio::{fs, process::Command};
+
+use crate::config::Flavor;
+
+use super::node_path::NodePath;
+
+/// Returns the file name of the archive at the given path or url.
+/// The query and fragment of urls aren't part of the name.
+pub fn archive_name(source: &str) -> Option<String> {
+    let name = if source.contains("://") {
+        Url::parse(source)
+            .ok()?
+            .path_segments()?
+            .next_back()?
+            .to_owned()
+    } else {
+        source.rsplit(['/', '\\']).next()?.to_owned()
+    };
+
+    (!name.is_empty()).then_some(name)
+}
+
+/// Returns the flavor of the build in the archive with the given name.
+/// Archives named like the published ones without the suffix of a flavor
+/// contain official builds unless unofficial builds are configured
+/// as they can't be told apart by their name.
+/// The configured flavor is used for all other archives.
+pub fn archive_flavor(archive_name: &str, configured: Flavor) -> Flavor {
+    let name = archive_name.to_lowercase();
+
+    Flavor::all()
+        .into_iter()
+        .filter(|f| !f.suffix().is_empty())
+        .find(|f| name.contains(&format!("{}.", f.suffix())))
+        .unwrap_or(match configured {
+            Flavor::Unofficial => Flavor::Unofficial,
+            _ if name.starts_with("node-v") => Flavor::Official,
+            _ => configured,
+        })
+}
+
+/// Returns the directory of the node build inside an extracted archive.
+/// Release archives contain a single directory named after the build
+/// but archives of custom builds might contain the build directly.
+#[tracing::instrument(level = "debug")]
+pub async fn find_build_root(dir: &Path) -> Result<Option<PathBuf>> {
+    if node_executable(dir).exists() {
+        return Ok(Some(dir.to_owned()));
+    }
+    let mut entries = fs::read_dir(dir).await.into_diagnostic()?;
+    let mut dirs = Vec::new();
+
+    while let Some(entry) = entries.next_entry().await.into_diagnostic()? {
+        if entry.file_type().await.into_diagnostic()?.is_dir() {
+            dirs.push(entry.path());
+        }
+    }
+
+    Ok(match dirs.as_slice() {
+        [root] if node_executable(root).exists() => Some(root.to_owned()),
+        _ => None,
+    })
+}
+
+/// Detects the version of the node build by running its executable.
+/// The version is read from the bundled headers if it can't be executed.
+#[tracing::instrument(level = "debug")]
+pub async fn detect_version(root: &Path) -> Option<Version> {
+    match version_from_executable(root).await {
+        Some(version) => Some(version),
+        None => version_from_headers(root).await,
+    }
+}
+
+/// Returns the version reported by the node executable of the build
+pub async fn version_from_executable(root: &Path) -> Option<Version> {
+    let output = Command::new(node_executable(root))
+        .arg("--version")
+        .output()
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        tracing::debug!("node --version exited with {}", output.status);
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    Version::parse(stdout.trim().trim_start_matches('v')).ok()
+}
+
+/// Reads the version from the `node_version.h` header of the build
+pub async fn version_from_headers(root: &Path) -> Option<Version> {
+    let header = fs::read_to_string(root.join("include/node/node_version.h"))
+        .await
+        .ok()?;
+    let define = |name: &str| {
+        header.lines().find_map(|l| {
+            l.trim()
+                .strip_prefix("#define")?
+                .trim()
+                .strip_prefix(name)?
+                .trim()
+                .parse::<u64>()
+                .ok()
+        })
+    };
+
+    Some(Version::new(
+        define("NODE_MAJOR_VERSION")?,
+        define("NODE_MINOR_VERSION")?,
+        define("NODE_PATCH_VERSION")?,
+    ))
+}
+
+fn node_executable(root: &Path) -> PathBuf {
+    NodePath::new(root.to_owned()).node()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_strips_the_query_and_fragment_from_archive_urls() {
+        assert_eq!(
+            archive_name("https://example.com/dist/node-v20.0.0.tar.gz?token=a/b#top").as_deref(),
+            Some("node-v20.0.0.tar.gz")
+        );
+        assert_eq!(
+            archive_name("file:///tmp/node.zip").as_deref(),
+            Some("node.zip")
+        );
+        assert_eq!(
+            archive_name("./builds/node.tar.xz").as_deref(),
+            Some("node.tar.xz")
+        );
+        assert_eq!(archive_name("https://example.com/"), None);
+    }
+
+    #[test]
+    fn it_detects_the_flavor_from_the_archive_name() {
+        let flavor = |name| archive_flavor(name, Flavor::Official);
+
+        assert_eq!(flavor("node-v20.0.0-linux-x64-musl.tar.xz"), Flavor::Musl);
+        assert_eq!(
+            flavor("node-v20.0.0-linux-x64-glibc-217.tar.gz"),
+            Flavor::Glibc217
+        );
+        assert_eq!(
+            flavor("node-v20.0.0-linux-x64-pointer-compression.tar.xz"),
+            Flavor::PointerCompression
+        );
+        assert_eq!(
+            archive_flavor("node-v20.0.0-linux-x64.tar.xz", Flavor::Musl),
+            Flavor::Official
+        );
+        assert_eq!(
+            archive_flavor("node-v20.0.0-linux-riscv64.tar.xz", Flavor::Unofficial),
+            Flavor::Unofficial
+        );
+        assert_eq!(archive_flavor("custom.tar.gz", Flavor::Musl), Flavor::Musl);
+    }
+}
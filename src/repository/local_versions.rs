@@ -1,23 +1,30 @@
-use std::{fs::File, io::Write};
+use std::fs;
 
 use semver::VersionReq;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    config::Flavor,
     consts::INSTALLED_VERSION_FILE,
-    versioning::{SimpleVersion, VersionMetadata},
+    lock::{write_atomic, FileLock},
+    utils::{deserialize_cache, serialize_cache},
+    versioning::{Channel, SimpleVersion, VersionMetadata},
 };
 use miette::{Context, IntoDiagnostic, Result};
 
+/// Installed versions are identified by their version and build flavor
+/// so that multiple flavors of the same version can be installed
+type InstalledKey = (SimpleVersion, Flavor);
+
 #[derive(Serialize, Deserialize, Default)]
 pub struct InstalledVersions {
-    ordered_versions: Vec<(SimpleVersion, VersionMetadata)>,
+    ordered_versions: Vec<(InstalledKey, VersionMetadata)>,
 }
 
 impl InstalledVersions {
-    pub fn new(mut versions: Vec<(SimpleVersion, VersionMetadata)>) -> Self {
-        versions.sort_by_key(|e| e.0);
-        versions.dedup_by_key(|e| e.0);
+    pub fn new(mut versions: Vec<(InstalledKey, VersionMetadata)>) -> Self {
+        versions.sort_by(|a, b| a.0.cmp(&b.0));
+        versions.dedup_by(|a, b| a.0 == b.0);
         Self {
             ordered_versions: versions,
         }
@@ -25,10 +32,10 @@ impl InstalledVersions {
 
     /// Loads the local versions
     pub fn load() -> Result<Self> {
-        let reader = File::open(&*INSTALLED_VERSION_FILE)
+        let contents = fs::read(&*INSTALLED_VERSION_FILE)
             .into_diagnostic()
-            .context("Opening local versions file")?;
-        let versions = bincode::deserialize_from(reader)
+            .context("Reading local versions file")?;
+        let versions = deserialize_cache(&contents)
             .into_diagnostic()
             .context("Deserializing local versions")?;
 
@@ -36,59 +43,107 @@ impl InstalledVersions {
     }
 
     /// Saves the local versions
-    pub fn save(&self) -> Result<()> {
-        let mut file = File::create(&*INSTALLED_VERSION_FILE)
-            .into_diagnostic()
-            .context("Opening local versions file")?;
-        bincode::serialize_into(&mut file, &self)
+    pub async fn save(&self) -> Result<()> {
+        let _lock = Self::lock().await?;
+        self.write()
+    }
+
+    /// Applies the change to the latest saved versions and saves them.
+    /// The file stays locked in between so that changes
+    /// of other processes aren't overwritten.
+    pub async fn update<F: FnOnce(&mut Self)>(&mut self, change: F) -> Result<()> {
+        let _lock = Self::lock().await?;
+
+        if let Ok(saved) = Self::load() {
+            *self = saved;
+        }
+        change(self);
+
+        self.write()
+    }
+
+    async fn lock() -> Result<FileLock> {
+        FileLock::exclusive("installed_versions", "updating the installed versions").await
+    }
+
+    fn write(&self) -> Result<()> {
+        let contents = serialize_cache(&self)
             .into_diagnostic()
             .context("Serializing local versions")?;
-        file.flush()
-            .into_diagnostic()
-            .context("Flushing local versions to file")?;
 
-        Ok(())
+        write_atomic(&INSTALLED_VERSION_FILE, &contents).context("Writing local versions file")
     }
 
     /// Inserts a new version. This requires reordering the list
-    pub fn insert(&mut self, version: (SimpleVersion, VersionMetadata)) {
+    pub fn insert(&mut self, version: (InstalledKey, VersionMetadata)) {
         self.ordered_versions.push(version);
-        self.ordered_versions.sort_by_key(|e| e.0);
-        self.ordered_versions.dedup_by_key(|e| e.0);
+        self.ordered_versions.sort_by(|a, b| a.0.cmp(&b.0));
+        self.ordered_versions.dedup_by(|a, b| a.0 == b.0);
     }
 
     /// Removes a version. This keeps the order intact
-    pub fn remove(&mut self, version: &SimpleVersion) {
-        self.ordered_versions.retain(|(v, _)| v != version)
+    pub fn remove(&mut self, key: &InstalledKey) {
+        self.ordered_versions.retain(|(k, _)| k != key)
     }
 
-    pub fn all(&self) -> Vec<&SimpleVersion> {
-        self.ordered_versions.iter().map(|(v, _)| v).collect()
-    }
-
-    pub fn lts<S: AsRef<str>>(&self, lts: S) -> Option<&VersionMetadata> {
+    pub fn all(&self) -> Vec<(Flavor, &VersionMetadata)> {
         self.ordered_versions
             .iter()
-            .filter_map(|(v, m)| Some((v, m.lts.clone()?, m)))
-            .filter(|(_, n, _)| n == lts.as_ref())
+            .map(|((_, f), m)| (*f, m))
+            .collect()
+    }
+
+    pub fn lts<S: AsRef<str>>(&self, lts: S, flavor: Flavor) -> Option<&VersionMetadata> {
+        self.with_flavor(flavor)
+            .filter(|m| {
+                m.lts
+                    .as_deref()
+                    .is_some_and(|l| l.eq_ignore_ascii_case(lts.as_ref()))
+            })
             .last()
-            .map(|(_, _, m)| m)
     }
 
-    pub fn fulfilling(&self, req: &VersionReq) -> Option<&VersionMetadata> {
+    /// Returns the latest installed release
+    pub fn latest(&self, flavor: Flavor) -> Option<&VersionMetadata> {
+        self.with_flavor(flavor)
+            .rfind(|m| m.version.channel().is_release())
+    }
+
+    /// Returns the latest installed lts release
+    pub fn latest_lts(&self, flavor: Flavor) -> Option<&VersionMetadata> {
+        self.with_flavor(flavor).rfind(|m| m.lts.is_some())
+    }
+
+    pub fn fulfilling(&self, req: &VersionReq, flavor: Flavor) -> Option<&VersionMetadata> {
+        self.with_flavor(flavor)
+            .rfind(|m| req.matches(&m.version.to_owned().into()))
+    }
+
+    /// Returns the latest installed version of the channel that fulfills the requirement
+    pub fn channel_fulfilling(
+        &self,
+        channel: Channel,
+        req: &VersionReq,
+        flavor: Flavor,
+    ) -> Option<&VersionMetadata> {
+        self.with_flavor(flavor)
+            .filter(|m| m.version.channel() == channel)
+            .rfind(|m| m.version.matches_loosely(req))
+    }
+
+    fn with_flavor(&self, flavor: Flavor) -> impl DoubleEndedIterator<Item = &VersionMetadata> {
         self.ordered_versions
             .iter()
-            .filter(|(v, _)| req.matches(&v.to_owned().into()))
-            .last()
+            .filter(move |((_, f), _)| *f == flavor)
             .map(|(_, m)| m)
     }
 }
 
-impl From<Vec<VersionMetadata>> for InstalledVersions {
-    fn from(versions: Vec<VersionMetadata>) -> Self {
+impl From<Vec<(Flavor, VersionMetadata)>> for InstalledVersions {
+    fn from(versions: Vec<(Flavor, VersionMetadata)>) -> Self {
         let versions = versions
             .into_iter()
-            .map(|v| (v.version.to_owned(), v))
+            .map(|(f, v)| ((v.version.to_owned(), f), v))
             .collect::<Vec<_>>();
 
         Self::new(versions)
@@ -1,29 +1,40 @@
 use core::fmt;
-use std::{path::PathBuf, str::FromStr};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use futures::future;
-use semver::{Version, VersionReq};
+use semver::{Comparator, Op, Version, VersionReq};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use tokio::fs;
 
 use crate::{
-    config::ConfigAccess,
+    config::{ArchiveFormat, ConfigAccess, Flavor},
     consts::{ARCH, BIN_DIR, CACHE_DIR, CFG_DIR, DATA_DIR, NODE_VERSIONS_DIR, OS},
-    error::VersionError,
-    versioning::{SimpleVersion, VersionMetadata},
+    error::{BundleError, ImportError, InvalidBuildError, VersionError},
+    lock::FileLock,
+    versioning::{Channel, SimpleVersion, VersionMetadata},
 };
 
 use miette::{Context, IntoDiagnostic, Result};
+use xkcd_unreachable::xkcd_unreachable;
 
 use self::{
-    downloader::{versions::Versions, NodeDownloader},
+    aliases::Aliases,
+    bundle::{BundleContent, BundleManifest, BundleRelease},
+    downloader::{schedule::ReleaseSchedule, versions::Versions, NodeDownloader},
     local_versions::InstalledVersions,
     node_path::NodePath,
 };
 
+pub mod aliases;
+pub mod bundle;
 pub mod downloader;
+mod import;
 mod local_versions;
 pub(crate) mod node_path;
+pub mod staging;
 
 #[derive(Clone, Debug)]
 pub enum NodeVersion {
@@ -31,6 +42,29 @@ pub enum NodeVersion {
     LatestLts,
     Lts(String),
     Req(VersionReq),
+    /// The latest version of a prerelease channel
+    /// that optionally fulfills the requirement
+    Channel(Channel, Option<VersionReq>),
+}
+
+impl NodeVersion {
+    /// Returns the requirement that only matches the given version
+    pub fn exact(version: &SimpleVersion) -> Self {
+        let req = VersionReq {
+            comparators: vec![Comparator {
+                op: Op::Exact,
+                major: version.major as u64,
+                minor: Some(version.minor as u64),
+                patch: Some(version.patch as u64),
+                pre: version.pre.clone(),
+            }],
+        };
+
+        match version.channel() {
+            Channel::Release => Self::Req(req),
+            channel => Self::Channel(channel, Some(req)),
+        }
+    }
 }
 
 impl FromStr for NodeVersion {
@@ -45,7 +79,24 @@ impl FromStr for NodeVersion {
             _ => {
                 let version_string = s.trim().trim_start_matches('v');
 
-                if let Ok(req) = VersionReq::parse(version_string) {
+                if let Some(channel) = parse_channel(&input) {
+                    Self::Channel(channel, None)
+                } else if let Some((channel, req)) = input
+                    .split_once('/')
+                    .and_then(|(channel, req)| Some((parse_channel(channel)?, req)))
+                {
+                    let req = VersionReq::parse(req.trim_start_matches('v'))
+                        .map_err(|_| "invalid version requirement for the channel")?;
+                    Self::Channel(channel, Some(req))
+                } else if let Some(version) = Version::parse(version_string)
+                    .ok()
+                    .filter(|v| !v.pre.is_empty())
+                {
+                    let channel = SimpleVersion::from(version.clone()).channel();
+                    let req = VersionReq::parse(&format!("={version}"))
+                        .map_err(|_| "invalid prerelease version")?;
+                    Self::Channel(channel, Some(req))
+                } else if let Ok(req) = VersionReq::parse(version_string) {
                     Self::Req(req)
                 } else {
                     Self::Lts(version_string.to_lowercase())
@@ -85,31 +136,95 @@ impl fmt::Display for NodeVersion {
             NodeVersion::LatestLts => String::from("lts"),
             NodeVersion::Lts(name) => name.to_owned(),
             NodeVersion::Req(req) => req.to_string(),
+            NodeVersion::Channel(channel, None) => channel.to_string(),
+            NodeVersion::Channel(channel, Some(req)) => match exact_prerelease(req) {
+                Some(version) => version,
+                None => format!("{channel}/{req}"),
+            },
         }
         .fmt(f)
     }
 }
 
+/// Parses the name of a prerelease channel
+fn parse_channel(name: &str) -> Option<Channel> {
+    Channel::from_str(name).ok().filter(|c| !c.is_release())
+}
+
+/// Returns the prerelease version if the requirement only matches this exact version
+fn exact_prerelease(req: &VersionReq) -> Option<String> {
+    let [comparator] = req.comparators.as_slice() else {
+        return None;
+    };
+
+    match comparator {
+        Comparator {
+            op: Op::Exact,
+            major,
+            minor: Some(minor),
+            patch: Some(patch),
+            pre,
+        } if !pre.is_empty() => Some(format!("{major}.{minor}.{patch}-{pre}")),
+        _ => None,
+    }
+}
+
+/// Something that can be installed
+#[derive(Clone, Debug)]
+pub enum InstallSource {
+    /// A version that is downloaded from the mirrors
+    Version(NodeVersion),
+    /// The path or url of a node archive
+    Archive(String),
+}
+
+impl FromStr for InstallSource {
+    type Err = &'static str;
+
+    /// Arguments are only treated as archives when they are urls, have the extension
+    /// of an archive or are explicit paths so that a file named like a version
+    /// doesn't shadow the version
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_path = s.starts_with("./") || s.starts_with("../") || Path::new(s).is_absolute();
+
+        if s.contains("://") || ArchiveFormat::from_file_name(s).is_some() || is_path {
+            Ok(Self::Archive(s.to_owned()))
+        } else {
+            NodeVersion::from_str(s).map(Self::Version)
+        }
+    }
+}
+
 pub struct Repository {
     downloader: NodeDownloader,
     installed_versions: InstalledVersions,
+    /// The build flavor that is installed and resolved
+    flavor: Flavor,
+    aliases: Aliases,
 }
 
 impl Repository {
     /// Initializes a new repository with the given confi
     #[tracing::instrument(level = "debug", skip_all)]
-    pub async fn init(config: ConfigAccess) -> Result<Self> {
+    pub async fn init(config: ConfigAccess, offline: bool) -> Result<Self> {
         Self::create_folders().await?;
-        let mut downloader = NodeDownloader::new(config.clone());
+        let mut downloader = NodeDownloader::new(config.clone(), offline).await?;
+        let flavor = config.get().await.download.flavor;
+        let aliases = Self::load_aliases(&config).await;
 
         let installed_versions = match InstalledVersions::load() {
             Ok(v) => v,
             Err(_) => {
+                let versions = match downloader.versions().await {
+                    Ok(versions) => Some(versions),
+                    Err(e) => {
+                        tracing::warn!("Versions index unavailable: {e}");
+                        None
+                    }
+                };
                 let installed: InstalledVersions =
-                    load_installed_versions_info(downloader.versions().await?)
-                        .await?
-                        .into();
-                installed.save()?;
+                    load_installed_versions_info(versions, flavor).await?.into();
+                installed.save().await?;
                 installed
             }
         };
@@ -117,9 +232,21 @@ impl Repository {
         Ok(Self {
             downloader,
             installed_versions,
+            flavor,
+            aliases,
         })
     }
 
+    /// Loads the configured aliases together with the lts codenames they can't shadow
+    async fn load_aliases(config: &ConfigAccess) -> Aliases {
+        let aliases = &config.get().await.aliases;
+        let schedule = ReleaseSchedule::load()
+            .await
+            .unwrap_or_else(ReleaseSchedule::bundled);
+
+        Aliases::new(aliases, schedule.codenames().cloned())
+    }
+
     #[tracing::instrument(level = "debug")]
     async fn create_folders() -> Result<()> {
         let dirs = vec![
@@ -138,7 +265,7 @@ impl Repository {
         }))
         .await
         .into_iter()
-        .fold(Result::Ok(()), |acc, res| acc.and(res))
+        .try_fold((), |_, res| res)
         .into_diagnostic()
         .wrap_err("Failed to create application directory")?;
 
@@ -148,89 +275,455 @@ impl Repository {
     /// Returns the path for the given node version
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn get_version_path(&mut self, version: &NodeVersion) -> Result<Option<NodePath>> {
-        let info = if let Ok(i) = self.lookup_local_version(version) {
-            i
-        } else {
-            self.lookup_remote_version(version).await?
-        };
-        let path = build_version_path(&info.version);
+        let info = self.lookup_version(version).await?;
+        let path = NodePath::new(build_version_path(&info.version, self.flavor));
 
-        Ok(if path.exists() {
-            Some(NodePath::new(path))
+        Ok(if path.node().exists() {
+            Some(path)
         } else {
             None
         })
     }
 
-    /// Returns a list of installed versions
-    pub fn installed_versions(&self) -> Vec<Version> {
-        self.installed_versions
-            .all()
-            .into_iter()
-            .map(|v| (*v).into())
-            .collect()
+    /// Returns a list of installed versions of all flavors
+    pub fn installed_versions(&self) -> Vec<(Flavor, &VersionMetadata)> {
+        self.installed_versions.all()
     }
 
     /// Returns if the given version is installed
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn is_installed(&mut self, version: &NodeVersion) -> Result<bool> {
-        let info = if let Ok(v) = self.lookup_local_version(version) {
-            v
-        } else {
-            self.lookup_remote_version(version).await?
-        };
+        let info = self.lookup_version(version).await?;
 
-        Ok(build_version_path(&info.version).exists())
+        Ok(self.is_build_installed(&info.version))
     }
 
     /// Installs the given node version
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn install_version(&mut self, version: &NodeVersion) -> Result<()> {
         let info = self.lookup_remote_version(version).await?.to_owned();
-        self.downloader.download(&info.version).await?;
-        self.installed_versions.insert((info.version, info));
-        self.installed_versions.save()?;
+        self.install_build(&info).await?;
+        let flavor = self.flavor;
+        self.installed_versions
+            .update(|v| v.insert(((info.version.clone(), flavor), info)))
+            .await?;
 
         Ok(())
     }
 
-    /// Uninstalls the given node version by deleting the versions directory
+    /// Installs the given node versions concurrently
+    /// and returns the result of each installation
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn install_versions(
+        &mut self,
+        versions: Vec<VersionMetadata>,
+    ) -> Result<Vec<(VersionMetadata, Result<()>)>> {
+        let results = future::join_all(versions.iter().map(|info| self.install_build(info))).await;
+        let results = versions.into_iter().zip(results).collect::<Vec<_>>();
+
+        let flavor = self.flavor;
+        self.installed_versions
+            .update(|v| {
+                for (info, _) in results.iter().filter(|(_, res)| res.is_ok()) {
+                    v.insert(((info.version.clone(), flavor), info.to_owned()));
+                }
+            })
+            .await?;
+
+        Ok(results)
+    }
+
+    /// Downloads the given version into a staging directory
+    /// and moves it into place once it has been validated
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn install_build(&self, info: &VersionMetadata) -> Result<()> {
+        let lock = self.lock_build(&info.version).await?;
+
+        if lock.waited() && self.is_build_installed(&info.version) {
+            tracing::debug!("v{} has been installed by another process", info.version);
+            return Ok(());
+        }
+        staging::sweep_stale();
+        let staging_dir = staging::staging_path(&info.version.to_string());
+        let result = self.stage_build(info, &staging_dir).await;
+
+        if staging_dir.exists() {
+            fs::remove_dir_all(&staging_dir)
+                .await
+                .into_diagnostic()
+                .context("Removing staging directory")?;
+        }
+
+        result
+    }
+
+    /// Locks the build of the given version so that
+    /// it isn't modified by multiple processes at once
+    async fn lock_build(&self, version: &SimpleVersion) -> Result<FileLock> {
+        FileLock::exclusive(
+            &format!("build-{}", build_dir_name(version, self.flavor)),
+            format!("installing {version}"),
+        )
+        .await
+    }
+
+    /// Returns if the build of the given version is installed
+    pub fn is_build_installed(&self, version: &SimpleVersion) -> bool {
+        NodePath::new(build_version_path(version, self.flavor))
+            .node()
+            .exists()
+    }
+
+    async fn stage_build(&self, info: &VersionMetadata, staging_dir: &Path) -> Result<()> {
+        self.downloader.download(info, staging_dir).await?;
+        let build_dir = staging_dir.join(build_dir_name(&info.version, self.flavor));
+        validate_build(&build_dir, &info.version).await?;
+
+        staging::move_into_place(&build_dir, &build_version_path(&info.version, self.flavor)).await
+    }
+
+    /// Installs the node archive at the given path or url.
+    /// The version is detected from the extracted build which is
+    /// registered with the flavor detected from the archive name.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn install_archive(&mut self, source: &str) -> Result<(VersionMetadata, Flavor)> {
+        let unknown_format = || ImportError::UnknownFormat {
+            archive: source.to_owned(),
+        };
+        let archive_name = import::archive_name(source).ok_or_else(unknown_format)?;
+        let format = ArchiveFormat::from_file_name(&archive_name).ok_or_else(unknown_format)?;
+        let flavor = import::archive_flavor(&archive_name, self.flavor);
+        let staging_dir = staging::staging_path("import");
+        let download_path = staging::staging_path(&archive_name);
+
+        let archive_path = if let Some(path) = source.strip_prefix("file://") {
+            PathBuf::from(path)
+        } else if source.contains("://") {
+            self.downloader.download_url(source, &download_path).await?;
+            download_path.to_owned()
+        } else {
+            PathBuf::from(source)
+        };
+        let result = self
+            .import_archive(source, archive_path, format, flavor, &staging_dir)
+            .await;
+
+        if staging_dir.exists() {
+            fs::remove_dir_all(&staging_dir)
+                .await
+                .into_diagnostic()
+                .context("Removing extracted archive")?;
+        }
+        if download_path.exists() {
+            fs::remove_file(&download_path)
+                .await
+                .into_diagnostic()
+                .context("Removing downloaded archive")?;
+        }
+        let info = result?;
+        self.installed_versions
+            .update(|v| v.insert(((info.version.clone(), flavor), info.to_owned())))
+            .await?;
+
+        Ok((info, flavor))
+    }
+
+    /// Extracts the archive into the staging directory and moves
+    /// the build to the path of the version it contains
+    async fn import_archive(
+        &mut self,
+        source: &str,
+        archive_path: PathBuf,
+        format: ArchiveFormat,
+        flavor: Flavor,
+        staging_dir: &Path,
+    ) -> Result<VersionMetadata> {
+        self.downloader
+            .extract_archive(archive_path, staging_dir.to_owned(), format)
+            .await?;
+        let root = import::find_build_root(staging_dir).await?.ok_or_else(|| {
+            ImportError::MissingExecutable {
+                archive: source.to_owned(),
+            }
+        })?;
+        let version =
+            import::detect_version(&root)
+                .await
+                .ok_or_else(|| ImportError::UnknownVersion {
+                    archive: source.to_owned(),
+                })?;
+        let simple_version = SimpleVersion::from(version.clone());
+        let _lock = self.lock_build(&simple_version).await?;
+        staging::move_into_place(&root, &build_version_path(&simple_version, flavor)).await?;
+
+        // the metadata is only known if the version has been released
+        let info = self
+            .downloader
+            .channel_versions(simple_version.channel())
+            .await
+            .ok()
+            .and_then(|versions| versions.get(&version).cloned())
+            .unwrap_or_else(|| VersionMetadata::unlisted(simple_version));
+
+        Ok(info)
+    }
+
+    /// Creates a bundle with the archives of the given versions and the
+    /// entries of the version index that are needed to resolve them without network access
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn create_bundle(
+        &mut self,
+        versions: &[NodeVersion],
+        output: &Path,
+    ) -> Result<BundleManifest> {
+        let mut infos: Vec<VersionMetadata> = Vec::new();
+
+        for version in versions {
+            let info = self.lookup_remote_version(version).await?.to_owned();
+
+            if !infos.iter().any(|i| i.version == info.version) {
+                infos.push(info);
+            }
+        }
+        let _lock = downloader::lock_cache().await?;
+        let mut releases = Vec::new();
+        let mut contents = Vec::new();
+        // the archives must not be evicted before they are written to the bundle
+        let mut archive_locks = Vec::new();
+
+        for info in &infos {
+            let (paths, archive_lock) = self.downloader.cache_release(info).await?;
+            archive_locks.push(archive_lock);
+            let file_names = paths.iter().map(|p| file_name(p)).collect::<Vec<_>>();
+            releases.push(BundleRelease {
+                version: info.version.clone(),
+                archive: file_names[0].to_owned(),
+            });
+            contents.extend(
+                file_names
+                    .iter()
+                    .zip(paths)
+                    .map(|(name, path)| (bundle::cache_file_name(name), BundleContent::File(path))),
+            );
+        }
+
+        for channel in release_channels(infos.iter().map(|i| &i.version)) {
+            let names = infos
+                .iter()
+                .filter(|i| i.version.channel() == channel)
+                .map(|i| format!("v{}", i.version))
+                .collect::<Vec<_>>();
+            let index = self
+                .downloader
+                .fetch_index(channel)
+                .await?
+                .into_iter()
+                .filter(|entry| {
+                    entry["version"]
+                        .as_str()
+                        .is_some_and(|v| names.iter().any(|n| n == v))
+                })
+                .collect::<Vec<_>>();
+            contents.push((
+                bundle::index_file_name(channel),
+                BundleContent::Bytes(serde_json::to_vec(&index).into_diagnostic()?),
+            ));
+        }
+
+        bundle::write(output, BundleManifest::new(self.flavor, releases), contents).await
+    }
+
+    /// Copies the files of a bundle into the download cache and adds its versions
+    /// to the cached version index. Returns the bundled versions.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn import_bundle(&mut self, path: &Path) -> Result<Vec<VersionMetadata>> {
+        let staging_dir = staging::staging_path("bundle");
+        let result = self.seed_from_bundle(path, &staging_dir).await;
+
+        if staging_dir.exists() {
+            fs::remove_dir_all(&staging_dir)
+                .await
+                .into_diagnostic()
+                .context("Removing extracted bundle")?;
+        }
+
+        result
+    }
+
+    async fn seed_from_bundle(
+        &mut self,
+        path: &Path,
+        staging_dir: &Path,
+    ) -> Result<Vec<VersionMetadata>> {
+        let manifest = bundle::extract(path, staging_dir).await?;
+        manifest.check_platform(self.flavor)?;
+
+        {
+            let _lock = downloader::lock_cache().await?;
+
+            for (file, name) in manifest.cache_files() {
+                let tmp_path = CACHE_DIR.join(format!(".{name}.import"));
+                fs::copy(staging_dir.join(&file.name), &tmp_path)
+                    .await
+                    .into_diagnostic()
+                    .with_context(|| format!("Copying {name} into the cache"))?;
+                fs::rename(&tmp_path, CACHE_DIR.join(name))
+                    .await
+                    .into_diagnostic()
+                    .with_context(|| format!("Moving {name} into the cache"))?;
+            }
+        }
+
+        for channel in release_channels(manifest.releases.iter().map(|r| &r.version)) {
+            let index_name = bundle::index_file_name(channel);
+
+            // only files that are listed in the manifest have been verified
+            if !manifest.files.iter().any(|f| f.name == index_name) {
+                return Err(BundleError::Corrupted {
+                    file: index_name,
+                    reason: String::from("isn't listed in the manifest"),
+                }
+                .into());
+            }
+            let contents = fs::read(staging_dir.join(&index_name))
+                .await
+                .into_diagnostic()?;
+            let versions = serde_json::from_slice(&contents)
+                .into_diagnostic()
+                .with_context(|| format!("Parsing bundled {index_name}"))?;
+            self.downloader.import_versions(channel, versions).await?;
+        }
+        let mut infos = Vec::new();
+
+        for release in manifest.releases {
+            let version = Version::from(release.version.clone());
+            let info = self
+                .downloader
+                .channel_versions(release.version.channel())
+                .await?
+                .get(&version)
+                .cloned()
+                .unwrap_or_else(|| VersionMetadata::unlisted(release.version));
+            infos.push(info);
+        }
+
+        Ok(infos)
+    }
+
+    /// Uninstalls the given node version by deleting its build directory.
+    /// The versions directory is deleted once no flavor of the version is left.
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn uninstall(&mut self, version: &NodeVersion) -> Result<()> {
         let info = self.lookup_local_version(version)?.clone();
-        let version_dir = NODE_VERSIONS_DIR.join(info.version.to_string());
+        let build_dir = build_version_path(&info.version, self.flavor);
+        let _lock = self.lock_build(&info.version).await?;
 
-        if !version_dir.exists() {
+        if !build_dir.exists() {
             return Err(VersionError::not_installed(version).into());
         }
 
-        fs::remove_dir_all(version_dir)
-            .await
-            .into_diagnostic()
-            .context("Deleting node version")?;
-        self.installed_versions.remove(&info.version);
-        self.installed_versions.save()?;
+        staging::remove(&build_dir).await?;
+
+        if let Some(version_dir) = build_dir.parent() {
+            let mut entries = fs::read_dir(version_dir).await.into_diagnostic()?;
+
+            if entries.next_entry().await.into_diagnostic()?.is_none() {
+                fs::remove_dir(version_dir)
+                    .await
+                    .into_diagnostic()
+                    .context("Deleting version directory")?;
+            }
+        }
+        let key = (info.version, self.flavor);
+        self.installed_versions.update(|v| v.remove(&key)).await?;
 
         Ok(())
     }
 
+    /// Performs a lookup for the given node version in the installed versions
+    /// and falls back to the known versions if it isn't installed
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn lookup_version(&mut self, version_req: &NodeVersion) -> Result<VersionMetadata> {
+        if let Ok(v) = self.lookup_local_version(version_req) {
+            Ok(v.to_owned())
+        } else {
+            Ok(self.lookup_remote_version(version_req).await?.to_owned())
+        }
+    }
+
     /// Performs a lookup for the given node version
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn lookup_remote_version(
         &mut self,
         version_req: &NodeVersion,
     ) -> Result<&VersionMetadata> {
+        let version_req = &self.aliases.resolve(version_req)?;
+
+        if let NodeVersion::Channel(channel, req) = version_req {
+            return self.lookup_channel_version(*channel, req.as_ref()).await;
+        }
         let versions = self.downloader.versions().await?;
 
         let version = match version_req {
-            NodeVersion::Latest => versions.latest(),
-            NodeVersion::LatestLts => versions.latest_lts(),
-            NodeVersion::Lts(lts) => versions
-                .get_lts(lts)
-                .ok_or_else(|| VersionError::unknown_version(lts.to_owned()))?,
-            NodeVersion::Req(req) => versions
-                .get_fulfilling(req)
-                .ok_or_else(|| VersionError::unfulfillable_version(req.to_owned()))?,
+            NodeVersion::Latest => versions
+                .latest()
+                .ok_or_else(|| VersionError::unavailable_for_platform(version_req))?,
+            NodeVersion::LatestLts => versions
+                .latest_lts()
+                .ok_or_else(|| VersionError::unavailable_for_platform(version_req))?,
+            NodeVersion::Lts(lts) => versions.get_lts(lts).ok_or_else(|| {
+                if versions.is_lts(lts) {
+                    VersionError::unavailable_for_platform(lts)
+                } else {
+                    VersionError::unknown_version(lts)
+                }
+            })?,
+            NodeVersion::Req(req) => versions.get_fulfilling(req).ok_or_else(|| {
+                if versions.is_fulfillable(req) {
+                    VersionError::unavailable_for_platform(req)
+                } else {
+                    VersionError::unfulfillable_version(req)
+                }
+            })?,
+            NodeVersion::Channel(..) => xkcd_unreachable!(),
+        };
+
+        Ok(version)
+    }
+
+    /// Performs a lookup for the latest version of the channel
+    /// that fulfills the optional requirement
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn lookup_channel_version(
+        &mut self,
+        channel: Channel,
+        req: Option<&VersionReq>,
+    ) -> Result<&VersionMetadata> {
+        let src = NodeVersion::Channel(channel, req.cloned());
+
+        if !self.flavor.is_official() {
+            return Err(VersionError::new(
+                src,
+                format!(
+                    "The {channel} channel is only available for official builds, not for {} builds.",
+                    self.flavor
+                ),
+            )
+            .into());
+        }
+        let versions = self.downloader.channel_versions(channel).await?;
+
+        let version = match req {
+            None => versions
+                .latest()
+                .ok_or_else(|| VersionError::unavailable_for_platform(&src))?,
+            Some(req) => versions.get_fulfilling(req).ok_or_else(|| {
+                if versions.is_fulfillable(req) {
+                    VersionError::unavailable_for_platform(&src)
+                } else {
+                    VersionError::unfulfillable_version(&src)
+                }
+            })?,
         };
 
         Ok(version)
@@ -239,20 +732,93 @@ impl Repository {
     /// Performs a lookup for the given node version
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn lookup_local_version(&self, version_req: &NodeVersion) -> Result<&VersionMetadata> {
+        let version_req = &self.aliases.resolve(version_req)?;
         let versions = &self.installed_versions;
         let version = match version_req {
+            NodeVersion::Latest => versions
+                .latest(self.flavor)
+                .ok_or_else(|| VersionError::not_installed(version_req))?,
+            NodeVersion::LatestLts => versions
+                .latest_lts(self.flavor)
+                .ok_or_else(|| VersionError::not_installed(version_req))?,
             NodeVersion::Lts(lts) => versions
-                .lts(lts)
+                .lts(lts, self.flavor)
                 .ok_or_else(|| VersionError::unknown_version(lts.to_owned()))?,
             NodeVersion::Req(req) => versions
-                .fulfilling(req)
+                .fulfilling(req, self.flavor)
                 .ok_or_else(|| VersionError::unfulfillable_version(req.to_owned()))?,
+            NodeVersion::Channel(channel, Some(req)) => versions
+                .channel_fulfilling(*channel, req, self.flavor)
+                .ok_or_else(|| VersionError::not_installed(version_req))?,
             _ => return Err(VersionError::unsupported(version_req.to_owned()).into()),
         };
 
         Ok(version)
     }
 
+    /// Returns the newest published version for `latest` and `lts` if it is newer
+    /// than the installed version they resolve to. Only the cached list of versions
+    /// is used so that the network is never accessed.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn published_update(&self, version_req: &NodeVersion) -> Option<VersionMetadata> {
+        let installed = self.lookup_local_version(version_req).ok()?;
+        let versions = Versions::load(self.flavor, Channel::Release).await?;
+        let published = match self.aliases.resolve(version_req).ok()? {
+            NodeVersion::Latest => versions.latest()?,
+            NodeVersion::LatestLts => versions.latest_lts()?,
+            _ => return None,
+        };
+
+        (published.version > installed.version).then(|| published.to_owned())
+    }
+
+    /// Returns the newest security release published after the given version.
+    /// Only the cached list of versions is used so that the network is never accessed.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn cached_security_update(&self, version: &SimpleVersion) -> Option<VersionMetadata> {
+        if !version.channel().is_release() {
+            return None;
+        }
+        let versions = Versions::load(self.flavor, Channel::Release).await?;
+
+        versions.security_update(version).cloned()
+    }
+
+    /// Returns the release schedule and revalidates it if it is expired
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn release_schedule(&self) -> ReleaseSchedule {
+        self.downloader.schedule().await
+    }
+
+    /// Returns the cached release schedule or the bundled one without accessing the network
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn cached_release_schedule(&self) -> ReleaseSchedule {
+        ReleaseSchedule::load()
+            .await
+            .unwrap_or_else(ReleaseSchedule::bundled)
+    }
+
+    /// Returns the user defined aliases for versions
+    pub fn aliases(&self) -> &Aliases {
+        &self.aliases
+    }
+
+    /// Returns the build flavor used by the repository
+    pub fn flavor(&self) -> Flavor {
+        self.flavor
+    }
+
+    /// Returns the downloader used to fetch node versions
+    pub fn downloader(&self) -> &NodeDownloader {
+        &self.downloader
+    }
+
+    /// Refreshes the list of known versions and returns if it has changed
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn refresh_versions(&mut self) -> Result<bool> {
+        self.downloader.refresh_versions().await
+    }
+
     /// Returns the reference to all known versions
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn all_versions(&mut self) -> Result<&Versions> {
@@ -260,25 +826,122 @@ impl Repository {
     }
 }
 
-fn build_version_path(version: &SimpleVersion) -> PathBuf {
+/// Returns the path of the given build.
+/// Each flavor of a version is extracted into its own directory
+/// inside the directory of the version.
+fn build_version_path(version: &SimpleVersion, flavor: Flavor) -> PathBuf {
     NODE_VERSIONS_DIR
         .join(version.to_string())
-        .join(format!("node-v{}-{}-{}", version, OS, ARCH))
+        .join(build_dir_name(version, flavor))
 }
 
-async fn load_installed_versions_info(versions: &Versions) -> Result<Vec<VersionMetadata>> {
+fn build_dir_name(version: &SimpleVersion, flavor: Flavor) -> String {
+    format!("node-v{}-{}-{}{}", version, OS, ARCH, flavor.suffix())
+}
+
+/// Returns the distinct channels the given versions have been published in
+fn release_channels<'a, I: Iterator<Item = &'a SimpleVersion>>(versions: I) -> Vec<Channel> {
+    let mut channels = Vec::new();
+
+    for channel in versions.map(SimpleVersion::channel) {
+        if !channels.contains(&channel) {
+            channels.push(channel);
+        }
+    }
+
+    channels
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Checks that the extracted build contains a node executable
+/// that reports the expected version.
+/// Builds that can't be run on this system like musl builds on glibc
+/// are checked with the version from their headers instead.
+async fn validate_build(build_dir: &Path, version: &SimpleVersion) -> Result<()> {
+    let error = |reason: String| InvalidBuildError {
+        version: version.to_string(),
+        reason,
+    };
+
+    if !NodePath::new(build_dir.to_owned()).node().exists() {
+        return Err(error(String::from("it doesn't contain a node executable")).into());
+    }
+    if let Some(reported) = import::version_from_executable(build_dir).await {
+        if SimpleVersion::from(reported.clone()) != *version {
+            return Err(error(format!("the node executable reports v{reported}")).into());
+        }
+        return Ok(());
+    }
+    let reported = import::version_from_headers(build_dir)
+        .await
+        .ok_or_else(|| {
+            error(String::from(
+                "the node executable can't be run and the headers don't contain its version",
+            ))
+        })?;
+
+    // the headers don't contain the prerelease tag
+    if (reported.major, reported.minor, reported.patch)
+        != (
+            version.major.into(),
+            version.minor.into(),
+            version.patch.into(),
+        )
+    {
+        return Err(error(format!("the headers report v{reported}")).into());
+    }
+
+    Ok(())
+}
+
+/// Loads the info for all builds in the versions directory.
+/// Without the known versions only the version numbers are available.
+/// Builds without a flavor suffix are assigned to the given flavor if it is unofficial.
+async fn load_installed_versions_info(
+    versions: Option<&Versions>,
+    flavor: Flavor,
+) -> Result<Vec<(Flavor, VersionMetadata)>> {
+    // official and unofficial builds share the same directory name
+    let plain_flavor = if flavor == Flavor::Unofficial {
+        Flavor::Unofficial
+    } else {
+        Flavor::Official
+    };
     let mut installed_versions = Vec::new();
     let mut iter = fs::read_dir(&*NODE_VERSIONS_DIR).await.into_diagnostic()?;
 
     while let Some(entry) = iter.next_entry().await.into_diagnostic()? {
-        if let Ok(version) = Version::parse(entry.file_name().to_string_lossy().as_ref()) {
-            installed_versions.push(version);
+        let Ok(version) = Version::parse(entry.file_name().to_string_lossy().as_ref()) else {
+            continue;
         };
+        let simple_version = version.clone().into();
+        let mut builds = fs::read_dir(entry.path()).await.into_diagnostic()?;
+
+        while let Some(build) = builds.next_entry().await.into_diagnostic()? {
+            let name = build.file_name().to_string_lossy().into_owned();
+            let build_flavor = Flavor::all()
+                .into_iter()
+                .filter(|f| !f.suffix().is_empty() || *f == plain_flavor)
+                .find(|f| build_dir_name(&simple_version, *f) == name);
+
+            if let Some(build_flavor) = build_flavor {
+                installed_versions.push((build_flavor, version.clone()));
+            }
+        }
     }
     let versions = installed_versions
         .into_iter()
-        .filter_map(|v| versions.get(&v))
-        .cloned()
+        .map(|(f, v)| {
+            let info = versions
+                .and_then(|versions| versions.get(&v).cloned())
+                .unwrap_or_else(|| VersionMetadata::unlisted(v.into()));
+            (f, info)
+        })
         .collect();
 
     Ok(versions)
@@ -30,6 +30,16 @@ impl NodePath {
         self.base.to_owned()
     }
 
+    #[cfg(not(windows))]
+    pub fn node(&self) -> PathBuf {
+        self.bin().join("node")
+    }
+
+    #[cfg(windows)]
+    pub fn node(&self) -> PathBuf {
+        self.bin().join("node.exe")
+    }
+
     pub fn node_modules(&self) -> PathBuf {
         self.lib().join("node_modules")
     }
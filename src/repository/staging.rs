@@ -0,0 +1,207 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process,
+};
+
+use lazy_static::lazy_static;
+use miette::{Context, IntoDiagnostic, Result};
+use tokio::fs;
+
+use crate::{
+    consts::{LOCKS_DIR, NODE_VERSIONS_DIR},
+    lock::{lock_path, FileLock},
+};
+
+const STAGING_PREFIX: &str = ".staging";
+const REMOVAL_PREFIX: &str = ".removing";
+const PROCESS_LOCK_PREFIX: &str = "process-";
+
+lazy_static! {
+    /// Held for as long as this process is running so that other processes
+    /// know that its staging files are still in use
+    static ref PROCESS_LOCK: Option<FileLock> =
+        match FileLock::try_exclusive(&process_lock_name(process::id())) {
+            Ok(lock) => lock,
+            Err(e) => {
+                tracing::error!("Failed to lock the staging files of this process: {e}");
+                None
+            }
+        };
+}
+
+/// Returns the path of a temporary file or directory owned by this process.
+/// Builds are extracted into staging directories and renamed into place once
+/// they are complete so that an interrupted installation never leaves a partial build behind.
+pub fn staging_path(name: &str) -> PathBuf {
+    lazy_static::initialize(&PROCESS_LOCK);
+    NODE_VERSIONS_DIR.join(format!("{STAGING_PREFIX}-{}-{name}", process::id()))
+}
+
+fn removal_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    lazy_static::initialize(&PROCESS_LOCK);
+
+    NODE_VERSIONS_DIR.join(format!("{REMOVAL_PREFIX}-{}-{name}", process::id()))
+}
+
+/// Moves a complete build to its destination.
+/// An existing build is moved away before it is deleted.
+#[tracing::instrument(level = "debug")]
+pub async fn move_into_place(src: &Path, dst: &Path) -> Result<()> {
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .into_diagnostic()
+            .context("Creating version directory")?;
+    }
+    let previous = if dst.exists() {
+        let previous = removal_path(dst);
+        fs::rename(dst, &previous)
+            .await
+            .into_diagnostic()
+            .context("Moving previous installation away")?;
+        Some(previous)
+    } else {
+        None
+    };
+    fs::rename(src, dst)
+        .await
+        .into_diagnostic()
+        .context("Moving build into the versions directory")?;
+
+    if let Some(previous) = previous {
+        fs::remove_dir_all(previous)
+            .await
+            .into_diagnostic()
+            .context("Deleting previous installation")?;
+    }
+
+    Ok(())
+}
+
+/// Removes a build by moving it out of place before deleting it
+/// so that a partially deleted build is never used
+#[tracing::instrument(level = "debug")]
+pub async fn remove(path: &Path) -> Result<()> {
+    let removal_path = removal_path(path);
+
+    fs::rename(path, &removal_path)
+        .await
+        .into_diagnostic()
+        .context("Moving node version away")?;
+    fs::remove_dir_all(&removal_path)
+        .await
+        .into_diagnostic()
+        .context("Deleting node version")?;
+
+    Ok(())
+}
+
+/// Deletes all staging files and directories of this process
+pub fn cleanup() {
+    let prefixes = [STAGING_PREFIX, REMOVAL_PREFIX].map(|p| format!("{p}-{}-", process::id()));
+    let Ok(entries) = std::fs::read_dir(&*NODE_VERSIONS_DIR) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        if prefixes.iter().any(|p| name.starts_with(p)) {
+            remove_entry(&entry.path());
+        }
+    }
+}
+
+/// Deletes the staging files and directories of processes that are no longer running.
+/// They are left behind when a process is killed or crashes during an installation.
+/// A process is known to have exited when its process lock can be acquired.
+#[tracing::instrument(level = "debug")]
+pub fn sweep_stale() {
+    let own_pid = process::id();
+    let mut stale: HashMap<u32, Vec<PathBuf>> = HashMap::new();
+
+    for entry in read_dir_names(&NODE_VERSIONS_DIR) {
+        let pid = [STAGING_PREFIX, REMOVAL_PREFIX].into_iter().find_map(|p| {
+            let (pid, _) = entry.strip_prefix(p)?.strip_prefix('-')?.split_once('-')?;
+            pid.parse::<u32>().ok()
+        });
+
+        if let Some(pid) = pid.filter(|pid| *pid != own_pid) {
+            stale
+                .entry(pid)
+                .or_default()
+                .push(NODE_VERSIONS_DIR.join(entry));
+        }
+    }
+    // processes that left no staging files behind still leave their lock
+    for entry in read_dir_names(&LOCKS_DIR) {
+        let pid = entry
+            .strip_prefix(PROCESS_LOCK_PREFIX)
+            .and_then(|e| e.strip_suffix(".lock"))
+            .and_then(|pid| pid.parse::<u32>().ok());
+
+        if let Some(pid) = pid.filter(|pid| *pid != own_pid) {
+            stale.entry(pid).or_default();
+        }
+    }
+
+    for (pid, paths) in stale {
+        let name = process_lock_name(pid);
+
+        match FileLock::try_exclusive(&name) {
+            Ok(Some(_lock)) => {
+                tracing::debug!("Removing the staging files of process {pid}");
+                paths.iter().for_each(|p| remove_entry(p));
+
+                if let Err(e) = std::fs::remove_file(lock_path(&name)) {
+                    tracing::debug!("Failed to remove the lock of process {pid}: {e}");
+                }
+            }
+            Ok(None) => tracing::debug!("Process {pid} is still running"),
+            Err(e) => tracing::error!("Failed to check if process {pid} is running: {e}"),
+        }
+    }
+}
+
+fn process_lock_name(pid: u32) -> String {
+    format!("{PROCESS_LOCK_PREFIX}{pid}")
+}
+
+fn read_dir_names(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn remove_entry(path: &Path) {
+    let result = if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    };
+
+    if let Err(e) = result {
+        tracing::error!("Failed to remove {path:?}: {e}");
+    }
+}
+
+/// Registers a handler that deletes the staging state
+/// when the process is interrupted
+pub fn register_interrupt_handler() -> Result<()> {
+    ctrlc::set_handler(|| {
+        cleanup();
+        process::exit(130);
+    })
+    .into_diagnostic()
+    .context("Registering interrupt handler")
+}
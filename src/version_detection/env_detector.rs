@@ -1,7 +1,5 @@
 use std::str::FromStr;
 
-use miette::{Context, IntoDiagnostic};
-
 use crate::repository::NodeVersion;
 
 use super::VersionDetector;
@@ -11,9 +9,8 @@ pub struct EnvDetector;
 #[async_trait::async_trait]
 impl VersionDetector for EnvDetector {
     async fn detect_version() -> miette::Result<Option<crate::repository::NodeVersion>> {
-        std::env::var("NODE_VERSION")
-            .into_diagnostic()
-            .context("Reading version from environment")
-            .map(|v| NodeVersion::from_str(&v).ok())
+        Ok(std::env::var("NODE_VERSION")
+            .ok()
+            .and_then(|v| NodeVersion::from_str(&v).ok()))
     }
 }
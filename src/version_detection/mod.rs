@@ -3,14 +3,15 @@ use async_trait::async_trait;
 use futures::future;
 use miette::Result;
 mod env_detector;
+mod nvmrc_detector;
 mod package_json_detector;
 mod version_file_detector;
 
-use crate::repository::NodeVersion;
+use crate::{error::InvalidNvmrcError, repository::NodeVersion};
 
 use self::{
-    env_detector::EnvDetector, package_json_detector::PackageJsonDetector,
-    version_file_detector::VersionFileDetector,
+    env_detector::EnvDetector, nvmrc_detector::NvmrcDetector,
+    package_json_detector::PackageJsonDetector, version_file_detector::VersionFileDetector,
 };
 
 #[async_trait]
@@ -18,21 +19,34 @@ pub trait VersionDetector {
     async fn detect_version() -> Result<Option<NodeVersion>>;
 }
 
+/// Runs all detectors at once and uses the version of the first one
+/// that found a version in the following order:
+///
+/// 1. the `.node-version` file
+/// 2. the `.nvmrc` file
+/// 3. the `engines.node` field of the `package.json`
+/// 4. the `NODE_VERSION` environment variable
+///
+/// The files are searched in the current and all parent directories.
+/// Errors of the detectors are ignored except for invalid lts versions
+/// in the `.nvmrc` file that would otherwise silently select another version.
 pub struct ParallelDetector;
 
 #[async_trait]
 impl VersionDetector for ParallelDetector {
     async fn detect_version() -> Result<Option<NodeVersion>> {
-        let version = future::join_all(vec![
+        future::join_all(vec![
             VersionFileDetector::detect_version(),
+            NvmrcDetector::detect_version(),
             PackageJsonDetector::detect_version(),
             EnvDetector::detect_version(),
         ])
         .await
         .into_iter()
-        .filter_map(Result::ok)
-        .find_map(|v| v);
-
-        Ok(version)
+        .find(|r| match r {
+            Ok(version) => version.is_some(),
+            Err(e) => e.downcast_ref::<InvalidNvmrcError>().is_some(),
+        })
+        .unwrap_or(Ok(None))
     }
 }
@@ -0,0 +1,159 @@
+use std::{path::Path, str::FromStr};
+
+use miette::{Context, IntoDiagnostic};
+use tokio::fs;
+
+use crate::{
+    config::ConfigAccess,
+    error::InvalidNvmrcError,
+    repository::{downloader::versions::Versions, NodeVersion},
+    utils::find_in_parents,
+    versioning::Channel,
+};
+
+use super::VersionDetector;
+
+/// Detects the version from the `.nvmrc` file used by nvm
+pub struct NvmrcDetector;
+
+#[async_trait::async_trait]
+impl VersionDetector for NvmrcDetector {
+    async fn detect_version() -> miette::Result<Option<crate::repository::NodeVersion>> {
+        let dir = std::env::current_dir().into_diagnostic()?;
+
+        if let Some(path) = find_in_parents(dir, ".nvmrc") {
+            let contents = fs::read_to_string(&path)
+                .await
+                .into_diagnostic()
+                .context("Reading .nvmrc file.")?;
+            let Some(line) = first_version_line(&contents) else {
+                return Ok(None);
+            };
+            let flavor = ConfigAccess::load().await?.get().await.download.flavor;
+            let versions = Versions::load(flavor, Channel::Release).await;
+            let lts_lines = versions.as_ref().map(Versions::lts_lines);
+
+            Ok(parse_nvm_version(&path, line, lts_lines.as_deref())?)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Returns the first line of the file that isn't empty or a comment
+fn first_version_line(contents: &str) -> Option<&str> {
+    contents
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or_default().trim())
+        .find(|l| !l.is_empty())
+}
+
+/// Parses a version in the syntax of nvm like `lts/*`, `lts/hydrogen`, `lts/-1` or `node`.
+/// The lts lines are ordered from newest to oldest and are `None`
+/// if the list of versions hasn't been fetched yet.
+fn parse_nvm_version(
+    path: &Path,
+    version: &str,
+    lts_lines: Option<&[&str]>,
+) -> Result<Option<NodeVersion>, InvalidNvmrcError> {
+    let version = version.to_lowercase();
+
+    match version.as_str() {
+        "node" | "stable" => return Ok(Some(NodeVersion::Latest)),
+        "lts/*" => return Ok(Some(NodeVersion::LatestLts)),
+        _ => {}
+    }
+    let Some(lts) = version.strip_prefix("lts/") else {
+        return Ok(NodeVersion::from_str(&version).ok());
+    };
+    let Some(offset) = lts.strip_prefix('-') else {
+        return Ok(Some(NodeVersion::Lts(lts.to_owned())));
+    };
+    let invalid = |reason: String| InvalidNvmrcError {
+        path: path.to_owned(),
+        value: version.to_owned(),
+        reason,
+    };
+
+    // the lts lines are counted backwards from the latest one
+    let offset = offset
+        .parse::<usize>()
+        .map_err(|_| invalid(format!("`{offset}` isn't a number of lts lines")))?;
+    let lines = lts_lines.ok_or_else(|| {
+        invalid(String::from(
+            "the list of versions hasn't been fetched yet. Run `nenv --use-version lts refresh` to fetch it",
+        ))
+    })?;
+    let codename = lines.get(offset).ok_or_else(|| {
+        invalid(format!(
+            "only the lts lines up to `lts/-{}` are known",
+            lines.len().saturating_sub(1)
+        ))
+    })?;
+
+    Ok(Some(NodeVersion::Lts((*codename).to_owned())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LTS_LINES: &[&str] = &["iron", "hydrogen", "gallium"];
+
+    fn parse(version: &str) -> Result<Option<NodeVersion>, InvalidNvmrcError> {
+        parse_nvm_version(Path::new(".nvmrc"), version, Some(LTS_LINES))
+    }
+
+    fn lts_name(version: &str) -> String {
+        match parse(version) {
+            Ok(Some(NodeVersion::Lts(name))) => name,
+            other => panic!("{version} wasn't parsed as a lts version: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn it_parses_latest_versions() {
+        assert!(matches!(parse("node"), Ok(Some(NodeVersion::Latest))));
+        assert!(matches!(parse("Stable"), Ok(Some(NodeVersion::Latest))));
+        assert!(matches!(parse("lts/*"), Ok(Some(NodeVersion::LatestLts))));
+    }
+
+    #[test]
+    fn it_parses_lts_codenames() {
+        assert_eq!(lts_name("lts/hydrogen"), "hydrogen");
+        assert_eq!(lts_name("LTS/Iron"), "iron");
+    }
+
+    #[test]
+    fn it_counts_lts_lines_backwards() {
+        assert_eq!(lts_name("lts/-0"), "iron");
+        assert_eq!(lts_name("lts/-1"), "hydrogen");
+        assert_eq!(lts_name("lts/-2"), "gallium");
+    }
+
+    #[test]
+    fn it_rejects_invalid_lts_offsets() {
+        let err = parse("lts/-3").unwrap_err();
+        assert!(err.reason.contains("lts/-2"), "{}", err.reason);
+
+        let err = parse("lts/-one").unwrap_err();
+        assert_eq!(err.value, "lts/-one");
+
+        assert!(parse_nvm_version(Path::new(".nvmrc"), "lts/-1", None).is_err());
+    }
+
+    #[test]
+    fn it_parses_plain_versions() {
+        assert!(matches!(parse("v20.11.0"), Ok(Some(NodeVersion::Req(_)))));
+        assert!(matches!(parse("20"), Ok(Some(NodeVersion::Req(_)))));
+    }
+
+    #[test]
+    fn it_skips_comments_and_empty_lines() {
+        assert_eq!(
+            first_version_line("# the node version\n\n  lts/iron # 20\n18\n"),
+            Some("lts/iron")
+        );
+        assert_eq!(first_version_line("# no version\n\n"), None);
+    }
+}
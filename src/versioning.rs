@@ -1,22 +1,118 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
+use semver::{BuildMetadata, Prerelease, VersionReq};
 use serde::{Deserialize, Serialize};
 
 use crate::repository::downloader::VersionInfo;
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize, Hash)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize, Hash)]
+#[serde(into = "semver::Version", from = "semver::Version")]
 pub struct SimpleVersion {
     pub major: u8,
     pub minor: u8,
     pub patch: u16,
+    /// The prerelease tag of versions from the nightly, rc, test and v8-canary channels
+    pub pre: Prerelease,
+    pub build: BuildMetadata,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+/// The release channels node versions are published in
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum Channel {
+    Release,
+    Nightly,
+    Rc,
+    Test,
+    V8Canary,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct VersionMetadata {
     /// The semver version
     pub version: SimpleVersion,
     /// The lts name of this version if it is an lts version
     pub lts: Option<String>,
+    /// The platforms and package types published for this version
+    pub files: Vec<String>,
+    /// The release date like `2024-01-09`
+    pub date: Option<String>,
+    /// Whether the release contains security fixes
+    pub security: bool,
+    /// The version of the bundled npm
+    pub npm: Option<String>,
+    /// The version of the bundled V8 engine
+    pub v8: Option<String>,
+}
+
+impl SimpleVersion {
+    /// Returns the channel the version has been published in
+    /// based on its prerelease tag
+    pub fn channel(&self) -> Channel {
+        let pre = self.pre.as_str();
+
+        [
+            Channel::Nightly,
+            Channel::Rc,
+            Channel::Test,
+            Channel::V8Canary,
+        ]
+        .into_iter()
+        .find(|c| pre.starts_with(c.name()))
+        .unwrap_or(Channel::Release)
+    }
+
+    /// Returns the version without the prerelease tag and build metadata
+    pub fn without_pre(&self) -> semver::Version {
+        semver::Version::new(self.major as u64, self.minor as u64, self.patch as u64)
+    }
+
+    /// Returns if the version fulfills the given requirement.
+    /// Unlike with plain semver matching, prereleases also fulfill
+    /// requirements that match their version without the prerelease tag
+    /// so that `22` matches `22.0.0-rc.1`.
+    pub fn matches_loosely(&self, req: &VersionReq) -> bool {
+        req.matches(&self.clone().into()) || req.matches(&self.without_pre())
+    }
+}
+
+impl Channel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Channel::Release => "release",
+            Channel::Nightly => "nightly",
+            Channel::Rc => "rc",
+            Channel::Test => "test",
+            Channel::V8Canary => "v8-canary",
+        }
+    }
+
+    pub fn is_release(&self) -> bool {
+        *self == Channel::Release
+    }
+}
+
+impl FromStr for Channel {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Channel::Release,
+            Channel::Nightly,
+            Channel::Rc,
+            Channel::Test,
+            Channel::V8Canary,
+        ]
+        .into_iter()
+        .find(|c| c.name() == s)
+        .ok_or(())
+    }
+}
+
+impl Display for Channel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.name().fmt(f)
+    }
 }
 
 impl From<semver::Version> for SimpleVersion {
@@ -25,13 +121,21 @@ impl From<semver::Version> for SimpleVersion {
             major: value.major as u8,
             minor: value.minor as u8,
             patch: value.patch as u16,
+            pre: value.pre,
+            build: value.build,
         }
     }
 }
 
 impl From<SimpleVersion> for semver::Version {
     fn from(value: SimpleVersion) -> Self {
-        Self::new(value.major as u64, value.minor as u64, value.patch as u64)
+        Self {
+            major: value.major as u64,
+            minor: value.minor as u64,
+            patch: value.patch as u64,
+            pre: value.pre,
+            build: value.build,
+        }
     }
 }
 
@@ -41,8 +145,35 @@ impl Display for SimpleVersion {
             major,
             minor,
             patch,
+            pre,
+            build,
         } = self;
-        write!(f, "{major}.{minor}.{patch}")
+        write!(f, "{major}.{minor}.{patch}")?;
+
+        if !pre.is_empty() {
+            write!(f, "-{pre}")?;
+        }
+        if !build.is_empty() {
+            write!(f, "+{build}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl VersionMetadata {
+    /// Returns the metadata of a version that isn't listed in the version index
+    /// so that only the version number is known
+    pub fn unlisted(version: SimpleVersion) -> Self {
+        Self {
+            version,
+            lts: None,
+            files: Vec::new(),
+            date: None,
+            security: false,
+            npm: None,
+            v8: None,
+        }
     }
 }
 
@@ -51,6 +182,11 @@ impl From<VersionInfo> for VersionMetadata {
         Self {
             version: value.version.into(),
             lts: value.lts.lts(),
+            files: value.files,
+            date: Some(value.date),
+            security: value.security,
+            npm: value.npm,
+            v8: Some(value.v8),
         }
     }
 }